@@ -0,0 +1,73 @@
+//! Lexer throughput benchmark.
+//!
+//! Run with `cargo bench --bench lexer`. Sources of growing size are lexed
+//! (and parsed) and the cost per byte is printed; it should stay flat as the
+//! input grows if lexing is linear.
+
+use firelang::compiler::firelang_lexer::lexer::{Lexer, TokenKind::Eof};
+use firelang::compiler::firelang_parser::ast::node::{Statement, Stmt};
+use firelang::compiler::firelang_parser::parser::Parser;
+use std::hint::black_box;
+use std::time::Instant;
+
+const SNIPPET: &str = r#"
+    // compute something
+    /* nested /* block */ comment */
+    let a = 31 + 10 * (3 - 1.5e3);
+    let s = "hello\tworld, 中文";
+    let c = '\u{1F600}';
+    return n;
+"#;
+
+fn source(size: usize) -> String {
+    let mut src = String::with_capacity(size + SNIPPET.len());
+    src.push_str("fn main(n <- int32) {");
+    while src.len() < size {
+        src.push_str(SNIPPET);
+    }
+    src.push('}');
+    src
+}
+
+fn lex(src: &str) -> usize {
+    let mut lexer = Lexer::new(src);
+    let mut count = 0;
+    while lexer.next_token().kind != Eof {
+        count += 1;
+    }
+    count
+}
+
+fn parse(src: &str) -> usize {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    match &module.items[..] {
+        [Stmt {
+            kind: Statement::FuncDecl { body, .. },
+            ..
+        }] if parser.errors().is_empty() => body.block.len(),
+        other => panic!("unexpected parse result: {other:?}"),
+    }
+}
+
+fn bench(name: &str, size: usize, f: fn(&str) -> usize) {
+    let src = source(size);
+    let begin = Instant::now();
+    let items = black_box(f(black_box(&src)));
+    let elapsed = begin.elapsed();
+    println!(
+        "{name:<6} {:>3} MB  {items:>9} items  {:>10.2?}  {:>6.2} ns/byte",
+        size >> 20,
+        elapsed,
+        elapsed.as_nanos() as f64 / src.len() as f64
+    );
+}
+
+fn main() {
+    for mb in [1, 2, 5, 10] {
+        bench("lex", mb << 20, lex);
+    }
+    for mb in [1, 2, 5, 10] {
+        bench("parse", mb << 20, parse);
+    }
+}
@@ -1,6 +1,12 @@
+use super::symbol::Symbol;
 use super::unescape::*;
+use crate::error_generator::generator::Diagnostics;
+use std::borrow::Cow;
 use std::fmt::Formatter;
-use std::str::Chars;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_security::{is_potential_mixed_script_confusable_char, MixedScript};
+use CharError::*;
+use InterpolationError::*;
 use LiteralKind::*;
 use NumBase::*;
 use RawStrError::*;
@@ -10,16 +16,26 @@ use TokenKind::*;
 /// Parse the whole language sourcefile
 #[derive(Debug, Clone)]
 pub struct Lexer<'a> {
-    pub src: String,
-    source: Chars<'a>,
-    prev: char,
+    pub src: &'a str,
+    /// The byte offset of the next char.
+    pos: usize,
+    /// The byte offset where the current token starts.
+    start: usize,
     pub line: usize,
     pub column: usize,
+    pub diagnostics: Diagnostics,
 }
 
 /// The end of file.
 pub const EOF: char = '\0';
 
+/// The byte-order mark, which is ignored at the start of the file.
+pub const BOM: char = '\u{FEFF}';
+
+/// How many bytes after a token the lexer may have looked at.
+/// It peeks at most one char, which is 4 bytes at most, leave some room.
+pub const MAX_LOOKAHEAD: usize = 16;
+
 /// All kinds of tokens in Fire.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum TokenKind {
@@ -35,8 +51,13 @@ pub enum TokenKind {
     Space,
     /// "// comment"
     LineComment,
+    /// "#!/usr/bin/env -S fire check" at the start of the file.
+    Shebang,
     /// "/* Comment */"
     BlockComment { expected: bool },
+    /// "/// Doc" or "/** Doc */", the content is the documentation of the
+    /// next function or variable. There are no module docs, "//!" is a line comment.
+    DocComment,
     /// Identifier or Keyword: "abc" or "int32"
     Ident,
     /// ### Literals
@@ -104,13 +125,13 @@ pub enum TokenKind {
 
 impl std::fmt::Debug for TokenKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.to_string().as_str())
+        write!(f, "{self}")
     }
 }
 
-impl ToString for TokenKind {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             Eof => "EOF",
             Ident => "<identifier>",
             Space => "<whitespace>",
@@ -139,49 +160,183 @@ impl ToString for TokenKind {
             Or => "'|'",
             Caret => "'^'",
             _ => "",
-        }
-        .into()
+        })
     }
 }
 
+/// A range of bytes in the source code.
+#[derive(Debug, Clone, Copy, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The content borrows the source code, unless it has been unescaped.
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
-pub struct Token {
+pub struct Token<'a> {
     pub kind: TokenKind,
-    pub content: String,
+    pub content: Cow<'a, str>,
+    pub span: Span,
+    /// Where the token starts, the columns count the chars from 1.
     pub line: usize,
     pub column: usize,
 }
 
+impl Token<'_> {
+    /// The name of an identifier, interned.
+    pub fn symbol(&self) -> Symbol {
+        Symbol::intern(&self.content)
+    }
+
+    /// Detach the token from the source code.
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            kind: self.kind,
+            content: Cow::Owned(self.content.into_owned()),
+            span: self.span,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+/// Where the parser gets its tokens from.
+pub trait TokenSource<'a> {
+    fn next_token(&mut self) -> Token<'a>;
+
+    /// The lexical diagnostics collected so far.
+    fn diagnostics(&self) -> &Diagnostics;
+
+    /// Where the lexical diagnostics are collected, for the parser to add the
+    /// ones of the source it lexes itself, like the embedded expressions.
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics;
+}
+
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub enum LiteralKind {
     /// 0xFFC66D, 0o1234567, 0b1010001010
-    Int {
-        base: NumBase,
-        dangling: bool,
-    },
+    Int { base: NumBase, dangling: bool },
     /// 0.12345, 1e10, 1e-10, 1e+10
-    Float {
-        dangling: bool,
-    },
+    Float { dangling: bool },
     /// 'a', '\n', '\x1b', '\u{1F600}'
     Char {
         unclose: bool,
         err: Option<UnescapeError>,
+        len: Option<CharError>,
+    },
+    /// b'a', b'\n', b'\xff'
+    Byte {
+        unclose: bool,
+        err: Option<UnescapeError>,
+        len: Option<CharError>,
+    },
+    /// b"raw bytes\xff"
+    ByteStr {
+        unclose: bool,
+        err: Option<UnescapeError>,
     },
     /// "a string", "string with \n", "\x1b[33m STRING!", "\u{58a8}\u{6c34}", "中文"
     Str {
         unclose: bool,
         err: Option<UnescapeError>,
     },
-    RawStr {
-        err: Option<RawStrError>,
+    /// "a + b = {a + b}", "{{ escaped braces }} and {value}"
+    InterpolatedStr {
+        parts: Vec<StrPart>,
+        err: Option<InterpolationError>,
+    },
+    /// r"(raw string)", r"sql(SELECT ")" FROM t)sql"
+    RawStr { err: Option<RawStrError> },
+}
+
+/// A segment of an interpolated string literal.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum StrPart {
+    /// The unescaped text between the embedded expressions.
+    Str(String),
+    /// The source of an embedded expression and the position right before it.
+    Expr {
+        src: String,
+        line: usize,
+        column: usize,
     },
 }
 
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum InterpolationError {
+    /// "{a + b"
+    UnclosedBrace,
+    /// "a + b}"
+    UnmatchedBrace,
+    /// "{}"
+    EmptyExpression,
+}
+
+impl InterpolationError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            UnclosedBrace => {
+                "there is not a '}' to close the embedded expression, use '{{' for a literal '{'"
+            }
+            UnmatchedBrace => "unmatched '}' in the string literal, use '}}' for a literal '}'",
+            EmptyExpression => {
+                "empty expression in the string literal, use '{{}}' for literal braces"
+            }
+        }
+    }
+}
+
+/// A character literal must contain exactly one unicode scalar value after unescaping,
+/// and a byte literal exactly one byte.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum CharError {
+    Empty,
+    MoreThanOneCodepoint,
+}
+
+impl CharError {
+    /// The message about a `what` literal, "character" or "byte".
+    pub fn message(&self, what: &str) -> String {
+        match self {
+            Empty => format!("empty {what} literal"),
+            MoreThanOneCodepoint if what == "byte" => {
+                "byte literal may only contain one byte".to_string()
+            }
+            MoreThanOneCodepoint => format!("{what} literal may only contain one codepoint"),
+        }
+    }
+}
+
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub enum RawStrError {
     UncloseString,
     UncloseParen,
+    TooLongDelimiter,
+    MismatchedDelimiter { expected: String, found: String },
+}
+
+impl RawStrError {
+    pub fn message(&self) -> String {
+        match self {
+            UncloseString => "the string literal is unclosed".into(),
+            UncloseParen => "missed a parentheses in the raw string literal".into(),
+            TooLongDelimiter => format!(
+                "the delimiter of the raw string literal cannot be more than \
+                 {MAX_RAW_STR_DELIMITER} characters"
+            ),
+            MismatchedDelimiter { expected, found } => format!(
+                "the raw string literal is unclosed: expected `){expected}\"` to close it, \
+                 found `){found}\"`"
+            ),
+        }
+    }
+}
+
+/// The longest user delimiter allowed in a raw string: r"delim(...)delim"
+pub const MAX_RAW_STR_DELIMITER: usize = 16;
+
+fn is_raw_str_delimiter(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '\\' | '"' | EOF)
 }
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
@@ -192,14 +347,64 @@ pub enum NumBase {
     Dec,
 }
 
+impl NumBase {
+    pub fn radix(&self) -> u32 {
+        match self {
+            Hex => 16,
+            Oct => 8,
+            Bin => 2,
+            Dec => 10,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Hex => "hexadecimal",
+            Oct => "octal",
+            Bin => "binary",
+            Dec => "decimal",
+        }
+    }
+}
+
+/// The digits of an integer literal, without the prefix like "0x".
+pub fn int_digits<'c>(content: &'c str, base: &NumBase) -> &'c str {
+    match base {
+        Dec => content,
+        _ => &content[2..],
+    }
+}
+
+/// The name and the maximum value of the type of an integer literal with `suffix`.
+pub fn int_range(suffix: &str) -> Option<(&'static str, u128)> {
+    Some(match suffix {
+        "b" => ("int8", i8::MAX as u128),
+        "u8" => ("uint8", u8::MAX as u128),
+        "i16" => ("int16", i16::MAX as u128),
+        "u16" => ("uint16", u16::MAX as u128),
+        "i32" => ("int32", i32::MAX as u128),
+        "u32" => ("uint32", u32::MAX as u128),
+        "i64" => ("int64", i64::MAX as u128),
+        "u64" => ("uint64", u64::MAX as u128),
+        "i128" => ("int128", i128::MAX as u128),
+        "u128" => ("uint128", u128::MAX),
+        // the type is inferred, it is checked with the types.
+        "" => ("integer", u128::MAX),
+        _ => return None,
+    })
+}
+
 impl Iterator for Lexer<'_> {
     type Item = char;
     fn next(&mut self) -> Option<Self::Item> {
-        let c = self.source.next();
+        let c = self.rest().chars().next();
 
-        self.prev = c.unwrap();
+        self.pos += c.map_or(0, char::len_utf8);
 
-        self.column += 1;
+        // "\r\n" takes the same columns as "\n".
+        if !c.is_some_and(is_zero_width) {
+            self.column += 1;
+        }
         if c == Some('\n') {
             self.line += 1;
             self.column = 0;
@@ -208,49 +413,171 @@ impl Iterator for Lexer<'_> {
     }
 }
 
-impl Lexer<'_> {
+impl<'a> TokenSource<'a> for Lexer<'a> {
+    fn next_token(&mut self) -> Token<'a> {
+        Lexer::next_token(self)
+    }
+
+    fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+}
+
+impl<'a> Lexer<'a> {
     /// Construct a new Lexer
-    pub fn new(src: &str) -> Lexer {
+    pub fn new(src: &'a str) -> Lexer<'a> {
         Lexer {
-            src: src.into(),
-            source: src.chars(),
-            prev: EOF,
+            src,
+            pos: 0,
+            start: 0,
             line: 1,
             column: 0,
+            diagnostics: Diagnostics::new(),
         }
     }
 
+    /// The byte offset of the next char.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
     /// Get next char without modifying the source code.
     fn lookahead(&self) -> char {
-        self.source.clone().next().unwrap_or(EOF)
+        self.rest().chars().next().unwrap_or(EOF)
+    }
+
+    /// The source code which is not lexed yet.
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// The source code of the current token.
+    fn slice(&self) -> &'a str {
+        &self.src[self.start..self.pos]
     }
 
     /// Eat the char until the returning value of `f` being true.
     fn eat_while(&mut self, mut f: impl FnMut(char) -> bool) {
-        while !f(self.lookahead()) && !self.source.as_str().is_empty() {
+        while !f(self.lookahead()) && !self.rest().is_empty() {
             self.next();
         }
     }
 
-    /// Get the previous char.
-    fn before(&self) -> char {
-        self.prev
+    /// Generate a token.
+    pub fn next_token(&mut self) -> Token<'a> {
+        let (line, column) = (self.line, self.column + 1);
+        self.start = self.pos;
+        let mut tok = self.scan_token();
+        tok.line = line;
+        tok.column = column;
+
+        if tok.kind == Ident {
+            self.check_ident(&tok.content, line, column);
+        }
+
+        if let Literal { kind, suffix } = &tok.kind {
+            let unterminated = match kind {
+                Char { unclose: true, .. } => Some("character"),
+                Byte { unclose: true, .. } => Some("byte"),
+                ByteStr { unclose: true, .. } => Some("byte string"),
+                Str { unclose: true, .. } => Some("string"),
+                RawStr {
+                    err: Some(UncloseString | MismatchedDelimiter { .. }),
+                } => Some("raw string"),
+                _ => None,
+            };
+
+            if let Some(name) = unterminated {
+                self.diagnostics
+                    .error(line, column, format!("unterminated {name} literal"))
+                    .note("reached the end of file while looking for the closing quote");
+            }
+
+            self.check_literal(kind, &tok.content, line, column);
+            self.check_number(kind, suffix, &tok.content, line, column);
+        }
+
+        tok
     }
 
-    /// Generate a token.
-    pub fn next_token(&mut self) -> Token {
-        if self.source.as_str().is_empty() {
+    /// Report the invalid escapes and lengths of a literal at `line` and
+    /// `column`, the literal is still a token.
+    fn check_literal(&mut self, kind: &LiteralKind, content: &str, line: usize, column: usize) {
+        let (message, note) = match kind {
+            Char { len: Some(len), .. } => (
+                len.message("character"),
+                (*len == MoreThanOneCodepoint).then(|| {
+                    format!("if you meant to write a string literal, use double quotes: \"{content}\"")
+                }),
+            ),
+
+            Char { err: Some(err), .. }
+            | Byte { err: Some(err), .. }
+            | ByteStr { err: Some(err), .. }
+            | Str { err: Some(err), .. } => (err.message().to_string(), None),
+
+            InterpolatedStr { err: Some(err), .. } => (err.message().to_string(), None),
+
+            // the unclosed ones are reported as unterminated.
+            RawStr {
+                err: Some(err @ (UncloseParen | TooLongDelimiter)),
+            } => (err.message(), None),
+
+            Byte { len: Some(len), .. } => (
+                len.message("byte"),
+                (*len == MoreThanOneCodepoint).then(|| {
+                    format!(
+                        "if you meant to write a byte string literal, use double quotes: b\"{content}\""
+                    )
+                }),
+            ),
+
+            _ => return,
+        };
+
+        let e = self.diagnostics.error(line, column, message);
+
+        if let Some(note) = note {
+            e.note(note);
+        }
+    }
+
+    fn scan_token(&mut self) -> Token<'a> {
+        if self.rest().is_empty() {
             return self.make_token(Eof, "End of file.");
         }
         let first = self.next().unwrap();
         match first {
             EOF => self.make_token(Eof, "End of file."),
+            BOM if self.line == 1 && self.column == 0 => self.make_token(Space, ""),
+            '#' if self.line == 1 && self.column == 1 && self.lookahead() == '!' => {
+                self.eat_while(|x| x == '\n');
+                self.make_token(Shebang, self.slice().trim_end_matches('\r'))
+            }
             c if c.is_whitespace() => self.whitespace(),
             '/' => match self.lookahead() {
                 '/' => self.line_comment(),
                 '*' => self.block_comment(),
                 _ => self.make_token(Slash, "/"),
             },
+            'b' => match self.lookahead() {
+                '\'' => {
+                    self.next();
+                    self.eat_byte()
+                }
+
+                '"' => {
+                    self.next();
+                    self.eat_byte_str()
+                }
+
+                _ => self.ident(),
+            },
+
             'r' => {
                 if self.lookahead() == '"' {
                     self.next();
@@ -264,7 +591,10 @@ impl Lexer<'_> {
             c @ '0'..='9' => {
                 let (kind, content) = self.number(c);
                 let suffix = self.get_suffix();
-                self.make_token(Literal { kind, suffix }, content.as_str())
+
+                let mut tok = self.make_token(Literal { kind, suffix }, content);
+                tok.span.end = self.start + content.len();
+                tok
             }
 
             '\'' => self.eat_char(),
@@ -294,46 +624,63 @@ impl Lexer<'_> {
             '&' => self.make_token(And, "&"),
             '|' => self.make_token(Or, "|"),
 
-            _ => self.make_token(Illegal, "unexpected"),
+            c => {
+                self.diagnostics
+                    .error(
+                        self.line,
+                        self.column,
+                        format!("unknown character `{c}` (U+{:04X})", c as u32),
+                    )
+                    .note("the character is skipped");
+
+                self.make_token(Illegal, self.slice())
+            }
         }
     }
 
     #[inline]
-    fn make_token(&self, kind: TokenKind, content: &str) -> Token {
+    fn make_token(&self, kind: TokenKind, content: impl Into<Cow<'a, str>>) -> Token<'a> {
         Token {
             kind,
             content: content.into(),
+            span: Span {
+                start: self.start,
+                end: self.pos,
+            },
             line: self.line,
             column: self.column,
         }
     }
 
-    fn whitespace(&mut self) -> Token {
+    fn whitespace(&mut self) -> Token<'a> {
         self.eat_while(|x| !x.is_whitespace());
         self.make_token(Space, " ")
     }
 
-    fn line_comment(&mut self) -> Token {
-        let (ln, col) = (self.line - 1, self.column - 1);
-
+    fn line_comment(&mut self) -> Token<'a> {
         self.eat_while(|x| x == '\n');
-        Token {
-            kind: LineComment,
-            content: "".into(),
-            line: ln,
-            column: col,
+
+        let text = self.slice().trim_end_matches('\r');
+
+        match text.strip_prefix("///") {
+            Some(doc) if !doc.starts_with('/') => {
+                self.make_token(DocComment, doc.strip_prefix(' ').unwrap_or(doc))
+            }
+            _ => self.make_token(LineComment, ""),
         }
     }
 
-    fn block_comment(&mut self) -> Token {
+    fn block_comment(&mut self) -> Token<'a> {
+        let (ln, col) = (self.line, self.column);
         self.next();
 
         let mut d = 1usize;
 
-        while let Some(x) = self.next() {
-            match x {
+        while self.lookahead() != EOF {
+            match self.next().unwrap() {
                 '/' if self.lookahead() == '*' => {
                     d += 1;
+                    self.next();
                 }
 
                 '*' if self.lookahead() == '/' => {
@@ -348,55 +695,83 @@ impl Lexer<'_> {
             }
         }
 
+        if d != 0 {
+            self.diagnostics
+                .error(ln, col, "unterminated block comment")
+                .note(format!(
+                    "reached the end of file while looking for {d} '*/' to close the comment"
+                ));
+        }
+
+        let text = self.slice();
+
+        if d == 0 && text.starts_with("/**") && !text.starts_with("/***") && text.len() > 4 {
+            return self.make_token(DocComment, block_doc(&text[3..text.len() - 2]));
+        }
+
         self.make_token(BlockComment { expected: d == 0 }, "")
     }
 
-    fn ident(&mut self) -> Token {
-        let mut c: String = self.before().into();
-
+    fn ident(&mut self) -> Token<'a> {
         while unicode_xid::UnicodeXID::is_xid_continue(self.lookahead()) {
-            // unexpected EOF
-            c.push(self.next().unwrap_or_else(|| {
-                panic!(
-                    "Error : Unexpected EOF at line {} column {}",
-                    self.line, self.column
-                )
-            }));
+            self.next();
         }
 
-        self.make_token(Ident, c.as_str())
+        // the same identifier may be written in different forms, like "é" and "e\u{301}".
+        let text = self.slice();
+        let ident: Cow<'a, str> = if is_nfc(text) {
+            text.into()
+        } else {
+            text.nfc().collect::<String>().into()
+        };
+
+        self.make_token(Ident, ident)
+    }
+
+    /// Warn at `line` and `column` if the identifier mixes scripts, like the
+    /// Cyrillic "а" in "аbc".
+    fn check_ident(&mut self, ident: &str, line: usize, column: usize) {
+        if !ident.is_single_script()
+            && ident
+                .chars()
+                .any(|c| !c.is_ascii() && is_potential_mixed_script_confusable_char(c))
+        {
+            self.diagnostics
+                .warning(
+                    line,
+                    column,
+                    format!("identifier `{ident}` mixes characters of different scripts"),
+                )
+                .note("some of them look like the characters of another script");
+        }
     }
 
-    fn number(&mut self, prev: char) -> (LiteralKind, String) {
+    fn number(&mut self, prev: char) -> (LiteralKind, &'a str) {
         let mut base: NumBase = Dec;
-        let mut number: String = prev.into();
 
         if prev == '0' {
-            let (empty, content) = match self.lookahead() {
+            let empty = match self.lookahead() {
                 'b' => {
                     base = Bin;
-                    number.push('b');
                     self.next();
                     self.eat_digit()
                 }
 
                 'o' => {
                     base = Oct;
-                    number.push('o');
                     self.next();
                     self.eat_digit()
                 }
 
                 'x' => {
                     base = Hex;
-                    number.push('x');
                     self.next();
                     self.eat_hex()
                 }
 
                 '0'..='9' | '.' | 'e' | 'E' => {
-                    let temp = self.eat_digit().1;
-                    (false, temp)
+                    self.eat_digit();
+                    false
                 }
 
                 _ => {
@@ -405,7 +780,7 @@ impl Lexer<'_> {
                             base: Dec,
                             dangling: false,
                         },
-                        "0".into(),
+                        self.slice(),
                     );
                 }
             };
@@ -416,7 +791,7 @@ impl Lexer<'_> {
                         base,
                         dangling: true,
                     },
-                    content,
+                    self.slice(),
                 );
             }
 
@@ -428,82 +803,72 @@ impl Lexer<'_> {
                         base,
                         dangling: false,
                     },
-                    number + &*content,
+                    self.slice(),
                 );
             }
         } else {
-            number += &*self.eat_digit().1;
+            self.eat_digit();
         }
 
-        match self.lookahead() {
+        let kind = match self.lookahead() {
             '.' => {
                 self.next();
-                number.push('.');
 
                 if self.lookahead().is_ascii_digit() {
-                    number += &*self.eat_digit().1;
+                    self.eat_digit();
                     match self.lookahead() {
                         'e' | 'E' => {
-                            number.push(self.next().unwrap());
-
-                            let tuple = self.eat_exponent();
+                            self.next();
 
-                            let dangling = tuple.0;
-                            number += &*tuple.1;
-
-                            (Float { dangling }, number)
+                            Float {
+                                dangling: self.eat_exponent(),
+                            }
                         }
-                        _ => (Float { dangling: false }, number),
+                        _ => Float { dangling: false },
                     }
                 } else {
-                    (Float { dangling: true }, number)
+                    Float { dangling: true }
                 }
             }
 
             'e' | 'E' => {
-                number.push(self.next().unwrap());
-
-                let tuple = self.eat_exponent();
-
-                let dangling = tuple.0;
-                number += &*tuple.1;
+                self.next();
 
-                (Float { dangling }, number)
+                Float {
+                    dangling: self.eat_exponent(),
+                }
             }
 
-            _ => (
-                Int {
-                    base,
-                    dangling: false,
-                },
-                number,
-            ),
-        }
+            _ => Int {
+                base,
+                dangling: false,
+            },
+        };
+
+        (kind, self.slice())
     }
 
-    fn eat_digit(&mut self) -> (bool, String) {
+    /// Returns true if there is no digit.
+    fn eat_digit(&mut self) -> bool {
         let mut dangling = true;
-        let mut content: String = "".into();
 
-        while let c @ '0'..='9' = self.lookahead() {
+        while self.lookahead().is_ascii_digit() {
             dangling = false;
-            content.push(c);
             self.next();
         }
 
-        (dangling, content)
+        dangling
     }
 
-    fn eat_hex(&mut self) -> (bool, String) {
+    fn eat_hex(&mut self) -> bool {
         let mut dangling = true;
-        let mut content: String = "".into();
-        while let c @ ('0'..='9' | 'A'..='F' | 'a'..='f') = self.lookahead() {
+
+        while self.lookahead().is_ascii_hexdigit() {
             dangling = false;
-            content.push(c);
             self.next();
         }
 
-        (dangling, content)
+        dangling
     }
 
     fn get_suffix(&mut self) -> String {
@@ -511,199 +876,496 @@ impl Lexer<'_> {
             return "".into();
         }
 
-        self.next();
+        // the separator in 12345_i64
+        if self.lookahead() == '_' {
+            self.next();
+        }
 
-        let mut result: String = self.next().unwrap().into();
+        let from = self.pos;
 
         while unicode_xid::UnicodeXID::is_xid_continue(self.lookahead()) {
-            result.push(self.next().unwrap());
+            self.next();
         }
 
-        result
+        self.src[from..self.pos].into()
     }
 
-    fn eat_exponent(&mut self) -> (bool, String) {
-        let mut res: String = "".into();
+    /// Report the invalid numbers at `line` and `column`, like `check_literal`.
+    fn check_number(
+        &mut self,
+        kind: &LiteralKind,
+        suffix: &str,
+        content: &str,
+        line: usize,
+        column: usize,
+    ) {
+        let (name, suffixes): (&str, &[&str]) = match kind {
+            Int { dangling, base } => {
+                if *dangling {
+                    self.diagnostics.error(
+                        line,
+                        column,
+                        format!("expected digits after `{content}`"),
+                    );
+                } else {
+                    self.check_int(base, suffix, content, line, column);
+                }
 
-        if self.lookahead() == '+' || self.lookahead() == '-' {
-            res.push(self.lookahead());
-            self.next();
+                (
+                    "integer",
+                    &[
+                        "", "b", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "i128", "u128",
+                    ],
+                )
+            }
+
+            Float { dangling } => {
+                // `0x1.5` and `0b1e5` are lexed as one float, not as an integer and a field.
+                let base = match content.get(..2) {
+                    Some("0x") => Some(Hex),
+                    Some("0o") => Some(Oct),
+                    Some("0b") => Some(Bin),
+                    _ => None,
+                };
+
+                if let Some(base) = base {
+                    self.diagnostics.error(
+                        line,
+                        column,
+                        format!("{} float literals are not supported", base.name()),
+                    );
+                } else if *dangling {
+                    self.diagnostics.error(
+                        line,
+                        column,
+                        format!("expected digits after `{content}`"),
+                    );
+                } else {
+                    self.check_float(suffix, content, line, column);
+                }
+
+                ("float", &["", "f32", "f64"])
+            }
+
+            _ => return,
+        };
+
+        if !suffixes.contains(&suffix) {
+            self.diagnostics
+                .error(
+                    line,
+                    column,
+                    format!("invalid suffix `{suffix}` for {name} literal `{content}`"),
+                )
+                .note(format!(
+                    "the suffix must be one of {}",
+                    suffixes[1..]
+                        .iter()
+                        .map(|x| format!("`{x}`"))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ));
         }
+    }
 
-        let tuple = self.eat_digit();
+    /// Report the digits out of the base and the values out of the range of the type.
+    fn check_int(
+        &mut self,
+        base: &NumBase,
+        suffix: &str,
+        content: &str,
+        line: usize,
+        column: usize,
+    ) {
+        let digits = int_digits(content, base);
+
+        if let Some(c) = digits.chars().find(|c| !c.is_digit(base.radix())) {
+            self.diagnostics.error(
+                line,
+                column,
+                format!("invalid digit `{c}` in {} literal `{content}`", base.name()),
+            );
+            return;
+        }
 
-        let dangling = tuple.0;
-        res += &*tuple.1;
+        let Some((ty, max)) = int_range(suffix) else {
+            return;
+        };
 
-        (dangling, res)
+        match u128::from_str_radix(digits, base.radix()) {
+            Ok(x) if x <= max => (),
+
+            Ok(_) => {
+                self.diagnostics
+                    .error(line, column, format!("literal out of range for `{ty}`"))
+                    .note(format!("the maximum value of `{ty}` is {max}"));
+            }
+
+            Err(_) => {
+                self.diagnostics
+                    .error(
+                        line,
+                        column,
+                        format!("integer literal `{content}` is too large"),
+                    )
+                    .note("the maximum value of an integer is 2^128 - 1");
+            }
+        }
+    }
+
+    /// Report the floats too large for their type, which would be infinite.
+    fn check_float(&mut self, suffix: &str, content: &str, line: usize, column: usize) {
+        let (ty, max, infinite) = match suffix {
+            "f32" => (
+                "float32",
+                format!("{:e}", f32::MAX),
+                content.parse::<f32>().is_ok_and(f32::is_infinite),
+            ),
+            _ => (
+                "float64",
+                format!("{:e}", f64::MAX),
+                content.parse::<f64>().is_ok_and(f64::is_infinite),
+            ),
+        };
+
+        if infinite {
+            self.diagnostics
+                .error(line, column, format!("literal out of range for `{ty}`"))
+                .note(format!("the maximum value of `{ty}` is {max}"));
+        }
+    }
+
+    fn eat_exponent(&mut self) -> bool {
+        if self.lookahead() == '+' || self.lookahead() == '-' {
+            self.next();
+        }
+
+        self.eat_digit()
     }
 
-    fn eat_char(&mut self) -> Token {
-        let mut unclose: bool = false;
-        let mut content: String = "".into();
+    /// Eat the content of a quoted literal and the closing quote,
+    /// returns `Err` with the content if the literal is unclosed.
+    fn eat_quoted(&mut self, quote: char) -> Result<&'a str, &'a str> {
+        let from = self.pos;
 
-        while self.lookahead() != '\'' {
+        while self.lookahead() != quote {
             if self.lookahead() == EOF {
-                unclose = true;
-                break;
+                return Err(&self.src[from..self.pos]);
             }
 
-            content.push(self.next().unwrap_or(EOF));
+            // keep the escaped quote in '\'' from closing the literal.
+            if self.next() == Some('\\') && self.lookahead() != EOF {
+                self.next();
+            }
         }
 
-        if unclose {
-            return self.make_token(
-                Literal {
-                    kind: Char { unclose, err: None },
-                    suffix: "".into(),
+        let content = &self.src[from..self.pos];
+        self.next();
+
+        Ok(content)
+    }
+
+    fn eat_char(&mut self) -> Token<'a> {
+        let content = match self.eat_quoted('\'') {
+            Ok(x) => x,
+
+            Err(x) => {
+                return self.make_token(
+                    Literal {
+                        kind: Char {
+                            unclose: true,
+                            err: None,
+                            len: None,
+                        },
+                        suffix: "".into(),
+                    },
+                    x,
+                )
+            }
+        };
+
+        let (err, len, content) = match unescape_cow(content) {
+            Err(err) => (Some(err), None, content.into()),
+
+            Ok(x) => match x.chars().count() {
+                0 => (None, Some(Empty), content.into()),
+                1 => (None, None, x),
+                // keep the source text, it is used to suggest a string literal.
+                _ => (None, Some(MoreThanOneCodepoint), content.into()),
+            },
+        };
+
+        self.make_token(
+            Literal {
+                kind: Char {
+                    unclose: false,
+                    err,
+                    len,
                 },
-                content.as_str(),
-            );
-        }
+                suffix: "".into(),
+            },
+            content,
+        )
+    }
 
-        if unescape(content.as_str()).is_err() {
-            let err = unescape(content.as_str()).unwrap_err();
-            let err = Some(err);
+    fn eat_byte(&mut self) -> Token<'a> {
+        let content = match self.eat_quoted('\'') {
+            Ok(x) => x,
+
+            Err(x) => {
+                return self.make_token(
+                    Literal {
+                        kind: Byte {
+                            unclose: true,
+                            err: None,
+                            len: None,
+                        },
+                        suffix: "".into(),
+                    },
+                    x,
+                )
+            }
+        };
 
-            return self.make_token(
-                Literal {
-                    kind: Char { unclose, err },
-                    suffix: "".into(),
+        let (err, len) = match unescape_bytes(content) {
+            Err(err) => (Some(err), None),
+
+            Ok(x) => match x.len() {
+                0 => (None, Some(Empty)),
+                1 => (None, None),
+                _ => (None, Some(MoreThanOneCodepoint)),
+            },
+        };
+
+        // the content is kept escaped, bytes above 0x7F cannot be stored in a string.
+        self.make_token(
+            Literal {
+                kind: Byte {
+                    unclose: false,
+                    err,
+                    len,
                 },
-                content.as_str(),
-            );
-        }
+                suffix: "".into(),
+            },
+            content,
+        )
+    }
 
-        self.next();
+    fn eat_byte_str(&mut self) -> Token<'a> {
+        let (unclose, content) = match self.eat_quoted('"') {
+            Ok(x) => (false, x),
+            Err(x) => (true, x),
+        };
+
+        let err = if unclose {
+            None
+        } else {
+            unescape_bytes(content).err()
+        };
 
         self.make_token(
             Literal {
-                kind: Char { unclose, err: None },
+                kind: ByteStr { unclose, err },
                 suffix: "".into(),
             },
-            unescape(content.as_str()).unwrap().as_str(),
+            content,
         )
     }
 
-    fn eat_str(&mut self) -> Token {
-        let mut unclose: bool = false;
-        let mut content: String = "".into();
+    fn eat_str(&mut self) -> Token<'a> {
+        if self.rest().starts_with("\"\"") {
+            return self.eat_text_block();
+        }
+
+        let (line, column) = (self.line, self.column);
+        let from = self.pos;
 
         while self.lookahead() != '"' {
             if self.lookahead() == EOF {
-                unclose = true;
-                break;
+                return self.make_token(
+                    Literal {
+                        kind: Str {
+                            unclose: true,
+                            err: None,
+                        },
+                        suffix: "".into(),
+                    },
+                    &self.src[from..self.pos],
+                );
             }
 
-            content.push(self.next().unwrap_or(EOF));
+            self.next();
         }
 
-        if unclose {
-            return self.make_token(
-                Literal {
-                    kind: Str { unclose, err: None },
-                    suffix: "".into(),
-                },
-                content.as_str(),
-            );
+        let content = &self.src[from..self.pos];
+        self.next();
+
+        self.make_str(normalize_newlines(content), line, column)
+    }
+
+    /// """
+    ///     A text block, the common indentation
+    ///     and the blank first and last line are stripped.
+    /// """
+    fn eat_text_block(&mut self) -> Token<'a> {
+        self.next();
+        self.next();
+
+        let (line, column) = (self.line, self.column);
+        let from = self.pos;
+
+        while !self.rest().starts_with("\"\"\"") {
+            if self.lookahead() == EOF {
+                return self.make_token(
+                    Literal {
+                        kind: Str {
+                            unclose: true,
+                            err: None,
+                        },
+                        suffix: "".into(),
+                    },
+                    &self.src[from..self.pos],
+                );
+            }
+
+            self.next();
         }
 
-        if unescape(content.as_str()).is_err() {
-            let err = unescape(content.as_str()).unwrap_err();
-            let err = Some(err);
+        let content = &self.src[from..self.pos];
 
-            return self.make_token(
+        self.next();
+        self.next();
+        self.next();
+
+        self.make_str(dedent(&normalize_newlines(content)).into(), line, column)
+    }
+
+    /// Make a string literal token from the content between the quotes.
+    fn make_str(&self, content: Cow<'a, str>, line: usize, column: usize) -> Token<'a> {
+        let str_token = |content| {
+            self.make_token(
                 Literal {
-                    kind: Str { unclose, err },
+                    kind: Str {
+                        unclose: false,
+                        err: None,
+                    },
                     suffix: "".into(),
                 },
-                content.as_str(),
-            );
+                content,
+            )
+        };
+
+        // nothing to unescape or interpolate, borrow the source.
+        if !content.contains(['\\', '{', '}']) {
+            return str_token(content);
         }
 
-        self.next();
+        let kind = match interpolate(&content, line, column) {
+            Ok(parts) if !parts.iter().any(|x| matches!(x, StrPart::Expr { .. })) => {
+                let text = parts
+                    .into_iter()
+                    .map(|x| match x {
+                        StrPart::Str(x) => x,
+                        StrPart::Expr { .. } => unreachable!(),
+                    })
+                    .collect::<String>();
+
+                return str_token(text.into());
+            }
+
+            Ok(parts) => InterpolatedStr { parts, err: None },
+
+            Err(kind) => kind,
+        };
 
         self.make_token(
             Literal {
-                kind: Str { unclose, err: None },
+                kind,
                 suffix: "".into(),
             },
-            unescape(content.as_str()).unwrap().as_str(),
+            content,
         )
     }
 
-    fn eat_raw_str(&mut self) -> Token {
-        if self.lookahead() != '(' {
-            let mut res: String = "".into();
-            let literal = Literal {
-                kind: RawStr {
-                    err: Some(UncloseParen),
-                },
-                suffix: "".into(),
-            };
+    fn eat_raw_str(&mut self) -> Token<'a> {
+        let raw_str_err = |err| Literal {
+            kind: RawStr { err: Some(err) },
+            suffix: "".into(),
+        };
 
-            res.push(self.lookahead());
+        let from = self.pos;
+
+        while is_raw_str_delimiter(self.lookahead()) {
             self.next();
+        }
+
+        let delimiter = &self.src[from..self.pos];
 
+        if self.lookahead() != '(' {
             while self.lookahead() != '"' {
                 if self.lookahead() == EOF {
-                    return self.make_token(literal, res.as_str());
+                    return self.make_token(raw_str_err(UncloseParen), &self.src[from..self.pos]);
                 }
 
-                res.push(self.lookahead());
                 self.next();
             }
 
+            let content = &self.src[from..self.pos];
             self.next();
-            return self.make_token(literal, res.as_str());
+            return self.make_token(raw_str_err(UncloseParen), content);
         }
 
-        let mut res: String = "".into();
         self.next();
 
-        loop {
+        let from = self.pos;
+        // the first `)delim"` whose delimiter does not match, reported if the literal never closes.
+        let mut mismatched: Option<&str> = None;
+
+        let content = loop {
             match self.lookahead() {
+                EOF => {
+                    let err = match mismatched {
+                        Some(found) => MismatchedDelimiter {
+                            expected: delimiter.into(),
+                            found: found.into(),
+                        },
+
+                        None => UncloseString,
+                    };
+
+                    return self.make_token(raw_str_err(err), &self.src[from..self.pos]);
+                }
+
                 ')' => {
+                    let content = &self.src[from..self.pos];
                     self.next();
-                    if self.lookahead() == '"' {
+
+                    let rest = self.rest();
+
+                    if rest.starts_with(delimiter) && rest[delimiter.len()..].starts_with('"') {
+                        self.eat_while(|x| x == '"');
                         self.next();
-                        break;
-                    } else {
-                        res.push(')');
+                        break content;
                     }
-                }
 
-                '"' => {
-                    self.next();
-                    return self.make_token(
-                        Literal {
-                            kind: RawStr {
-                                err: Some(UncloseParen),
-                            },
-                            suffix: "".into(),
-                        },
-                        "",
-                    );
-                }
+                    if mismatched.is_none() {
+                        let found = rest.trim_start_matches(is_raw_str_delimiter);
+                        let found = &rest[..rest.len() - found.len()];
 
-                EOF => {
-                    return self.make_token(
-                        Literal {
-                            kind: RawStr {
-                                err: Some(UncloseString),
-                            },
-                            suffix: "".into(),
-                        },
-                        "",
-                    )
+                        if rest[found.len()..].starts_with('"') {
+                            mismatched = Some(found);
+                        }
+                    }
                 }
 
-                x => {
-                    res.push(x);
+                _ => {
+                    self.next();
                 }
             }
+        };
 
-            self.next();
+        if delimiter.chars().count() > MAX_RAW_STR_DELIMITER {
+            return self.make_token(raw_str_err(TooLongDelimiter), content);
         }
 
         self.make_token(
@@ -711,7 +1373,191 @@ impl Lexer<'_> {
                 kind: RawStr { err: None },
                 suffix: "".into(),
             },
-            res.as_str(),
+            normalize_newlines(content),
         )
     }
 }
+
+/// The chars which take no column: the carriage return, so that a file with "\r\n"
+/// has the same positions as the one with "\n", and the byte-order mark.
+pub fn is_zero_width(c: char) -> bool {
+    c == '\r' || c == BOM
+}
+
+/// The number of columns taken by `text`.
+pub fn columns(text: &str) -> usize {
+    text.chars().filter(|&c| !is_zero_width(c)).count()
+}
+
+/// Turn the "\r\n" in a literal into "\n", the value does not depend on the line endings.
+fn normalize_newlines(text: &str) -> Cow<'_, str> {
+    if text.contains("\r\n") {
+        text.replace("\r\n", "\n").into()
+    } else {
+        text.into()
+    }
+}
+
+/// Strip the leading `*` of each line and the blank lines around a block doc comment.
+fn block_doc(content: &str) -> String {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|x| {
+            let x = x.trim_start();
+            let x = x.strip_prefix('*').unwrap_or(x);
+            x.strip_prefix(' ').unwrap_or(x).trim_end()
+        })
+        .collect();
+
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Strip the blank first and last line of a text block and the indentation common to all of
+/// its non-blank lines.
+fn dedent(content: &str) -> String {
+    let mut lines: Vec<&str> = content.split('\n').collect();
+
+    if lines.len() > 1 && lines[0].trim().is_empty() {
+        lines.remove(0);
+    }
+
+    if lines.len() > 1 && lines[lines.len() - 1].trim().is_empty() {
+        lines.pop();
+    }
+
+    let mut indent: Option<&str> = None;
+
+    for x in lines.iter().filter(|x| !x.trim().is_empty()) {
+        let ws = &x[..x.len() - x.trim_start().len()];
+
+        indent = Some(match indent {
+            None => ws,
+            Some(y) => {
+                let common = y.chars().zip(ws.chars()).take_while(|(a, b)| a == b);
+                &y[..common.map(|(a, _)| a.len_utf8()).sum()]
+            }
+        });
+    }
+
+    let indent = indent.unwrap_or("");
+
+    lines
+        .iter()
+        .map(|x| x.strip_prefix(indent).unwrap_or(x.trim_start()))
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Split the content of a string literal into unescaped text and embedded expressions.
+/// `line` and `column` are the position of the opening quote, errors are returned as the kind of the literal.
+fn interpolate(content: &str, line: usize, column: usize) -> Result<Vec<StrPart>, LiteralKind> {
+    let interpolation_err = |err| InterpolatedStr {
+        parts: vec![],
+        err: Some(err),
+    };
+    let unescape_err = |err| Str {
+        unclose: false,
+        err: Some(err),
+    };
+
+    let mut parts: Vec<StrPart> = Vec::new();
+    let mut text: String = "".into();
+    let mut chars = content.chars().peekable();
+
+    let mut pos = (line, column);
+
+    fn advance(pos: &mut (usize, usize), c: char) {
+        if !is_zero_width(c) {
+            pos.1 += 1;
+        }
+        if c == '\n' {
+            pos.0 += 1;
+            pos.1 = 0;
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        advance(&mut pos, c);
+
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                advance(&mut pos, chars.next().unwrap());
+                text.push('{');
+            }
+
+            '}' if chars.peek() == Some(&'}') => {
+                advance(&mut pos, chars.next().unwrap());
+                text.push('}');
+            }
+
+            '}' => return Err(interpolation_err(UnmatchedBrace)),
+
+            '{' => {
+                let (expr_line, expr_column) = pos;
+                let mut src: String = "".into();
+                let mut depth = 1usize;
+
+                loop {
+                    let x = chars.next().ok_or(interpolation_err(UnclosedBrace))?;
+                    advance(&mut pos, x);
+
+                    match x {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => (),
+                    }
+
+                    src.push(x);
+                }
+
+                if src.trim().is_empty() {
+                    return Err(interpolation_err(EmptyExpression));
+                }
+
+                if !text.is_empty() {
+                    parts.push(StrPart::Str(unescape(text.as_str()).map_err(unescape_err)?));
+                    text.clear();
+                }
+
+                parts.push(StrPart::Expr {
+                    src,
+                    line: expr_line,
+                    column: expr_column,
+                });
+            }
+
+            '\\' => {
+                text.push(c);
+
+                if let Some(x) = chars.next() {
+                    advance(&mut pos, x);
+                    text.push(x);
+
+                    if x == 'u' {
+                        for x in chars.by_ref() {
+                            advance(&mut pos, x);
+                            text.push(x);
+
+                            if x == '}' {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            _ => text.push(c),
+        }
+    }
+
+    if !text.is_empty() || parts.is_empty() {
+        parts.push(StrPart::Str(unescape(text.as_str()).map_err(unescape_err)?));
+    }
+
+    Ok(parts)
+}
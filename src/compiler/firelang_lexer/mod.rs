@@ -1,2 +1,4 @@
 pub mod lexer;
+pub mod stream;
+pub mod symbol;
 pub mod unescape;
@@ -0,0 +1,157 @@
+use super::lexer::{Lexer, Token, TokenSource, MAX_LOOKAHEAD};
+use crate::error_generator::generator::Diagnostics;
+use std::io::{ErrorKind, Read};
+
+/// The size of the chunks read from the input.
+pub const CHUNK_SIZE: usize = 8 * 1024;
+
+/// StreamLexer Struct
+/// Lex the source code read from an `io::Read` chunk by chunk, without
+/// loading the whole input first.
+///
+/// The tokens own their content, and their spans are the byte offsets in the
+/// whole stream.
+pub struct StreamLexer<R: Read> {
+    input: R,
+    /// The decoded source code which is read but not dropped yet.
+    window: String,
+    /// The byte offset in the window where the next token starts.
+    start: usize,
+    /// The tail of the last chunk which is not a complete UTF-8 sequence.
+    pending: Vec<u8>,
+    /// The byte offset of the window in the stream.
+    offset: usize,
+    eof: bool,
+    pub line: usize,
+    pub column: usize,
+    pub diagnostics: Diagnostics,
+}
+
+impl<R: Read> StreamLexer<R> {
+    /// Construct a new StreamLexer
+    pub fn new(input: R) -> StreamLexer<R> {
+        StreamLexer {
+            input,
+            window: String::new(),
+            start: 0,
+            pending: Vec::new(),
+            offset: 0,
+            eof: false,
+            line: 1,
+            column: 0,
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    /// Generate a token.
+    ///
+    /// A failed read is reported as a diagnostic and ends the stream.
+    pub fn next_token(&mut self) -> Token<'static> {
+        loop {
+            let rest = &self.window[self.start..];
+            let mut lexer = Lexer::new(rest);
+            lexer.line = self.line;
+            lexer.column = self.column;
+
+            let tok = lexer.next_token();
+            let len = lexer.offset();
+
+            // a token touching the end of the window may go on in the next chunk.
+            if self.eof || len + MAX_LOOKAHEAD <= rest.len() {
+                let mut tok = tok.into_owned();
+                tok.span.start += self.offset + self.start;
+                tok.span.end += self.offset + self.start;
+
+                self.line = lexer.line;
+                self.column = lexer.column;
+                self.diagnostics.extend(lexer.diagnostics);
+                self.start += len;
+
+                return tok;
+            }
+
+            // read at least as much as the window holds, so that a long token
+            // is not relexed for every chunk.
+            let want = rest.len().max(CHUNK_SIZE);
+
+            if let Err(e) = self.fill(want) {
+                self.diagnostics
+                    .error(
+                        self.line,
+                        self.column + 1,
+                        format!("failed to read the input: {e}"),
+                    )
+                    .note("the rest of the input is skipped");
+                self.eof = true;
+            }
+        }
+    }
+
+    /// Read and decode at least `want` bytes into the window, unless the input ends.
+    fn fill(&mut self, want: usize) -> std::io::Result<()> {
+        // drop the lexed source code before growing the window.
+        self.window.drain(..self.start);
+        self.offset += self.start;
+        self.start = 0;
+
+        let mut buf = vec![0; CHUNK_SIZE];
+        let mut read = 0;
+
+        while read < want {
+            let n = match self.input.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if n == 0 {
+                self.eof = true;
+
+                if !self.pending.is_empty() {
+                    self.pending.clear();
+                    return Err(invalid_utf8());
+                }
+
+                return Ok(());
+            }
+
+            read += n;
+            self.pending.extend_from_slice(&buf[..n]);
+
+            // a sequence split by the chunk boundary is kept for the next chunk.
+            let (valid, invalid) = match std::str::from_utf8(&self.pending) {
+                Ok(s) => (s.len(), false),
+                Err(e) => (e.valid_up_to(), e.error_len().is_some()),
+            };
+
+            let rest = self.pending.split_off(valid);
+            self.window
+                .push_str(std::str::from_utf8(&self.pending).unwrap());
+            self.pending = rest;
+
+            if invalid {
+                return Err(invalid_utf8());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_utf8() -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+}
+
+impl<R: Read> TokenSource<'static> for StreamLexer<R> {
+    fn next_token(&mut self) -> Token<'static> {
+        StreamLexer::next_token(self)
+    }
+
+    fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+}
@@ -0,0 +1,88 @@
+use once_cell::sync::Lazy;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Mutex;
+
+/// An interned identifier, two symbols are the same name if they are equal.
+///
+/// The names live as long as the program, so a symbol is a cheap key for the
+/// symbol tables, and the same name is stored once however often it is used.
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Symbol(u32);
+
+/// The names of all the symbols, shared by the whole program.
+static INTERNER: Lazy<Mutex<Interner>> = Lazy::new(|| Mutex::new(Interner::default()));
+
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    names: Vec<&'static str>,
+}
+
+impl Symbol {
+    pub fn intern(name: &str) -> Symbol {
+        let mut interner = INTERNER.lock().unwrap();
+
+        if let Some(&x) = interner.symbols.get(name) {
+            return x;
+        }
+
+        // never freed, the symbols may be used till the end.
+        let name: &'static str = Box::leak(name.into());
+        let x = Symbol(interner.names.len() as u32);
+
+        interner.names.push(name);
+        interner.symbols.insert(name, x);
+        x
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        INTERNER.lock().unwrap().names[self.0 as usize]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Symbol {
+        Symbol::intern(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Symbol {
+        Symbol::intern(&name)
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The same as the name, like `"x"`.
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// In the order of the names, not of the interning.
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self == other {
+            true => Ordering::Equal,
+            false => self.as_str().cmp(other.as_str()),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use UnescapeError::*;
 
@@ -15,6 +16,42 @@ pub enum UnescapeError {
     TooShortEscape,
     InvalidCharInHex,
     ValueOutOfHex,
+    NonAsciiInByte,
+    UnicodeInByte,
+}
+
+impl UnescapeError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            OnlyOneSlashError => r"only one '\' in character literal",
+            IllegalEscape => "there are some illegal characters in the escape sequence",
+            EmptyUnicode => "unexpected empty unicode escape sequence",
+            UnclosedUnicode => "there is not a '}' to close the escape sequence",
+            IllegalUnicode => {
+                "there is an illegal unicode escape sequence in the character literal"
+            }
+            TooLongUnicode => "too long value in the unicode escape sequence",
+            ValueOutOfUnicode => "value out of range: unicode value cannot be more than 10FFFF",
+            LoneSurrogate => "there is a lone surrogate codepoint in the unicode character",
+            InvalidCharInUnicode => "there is an invalid character in the unicode escape sequence",
+            TooShortEscape => "too short escape sequence in ascii escape character",
+            InvalidCharInHex => "there is an invalid character in the ascii escape sequence",
+            ValueOutOfHex => "value out of range: ascii escape value cannot be more than 0x7F",
+            NonAsciiInByte => {
+                "non-ASCII character in byte literal, use a '\\x' escape sequence instead"
+            }
+            UnicodeInByte => "unicode escape sequence cannot be used in byte literal",
+        }
+    }
+}
+
+/// Unescape the input, borrows it if there is no escape sequence.
+pub fn unescape_cow(input: &str) -> Result<Cow<'_, str>, UnescapeError> {
+    if !input.contains('\\') {
+        return Ok(input.into());
+    }
+
+    unescape(input).map(Cow::Owned)
 }
 
 pub fn unescape(input: &str) -> Result<String, UnescapeError> {
@@ -106,3 +143,128 @@ pub fn unescape(input: &str) -> Result<String, UnescapeError> {
 
     Ok(res)
 }
+
+/// Unescape the content of a byte literal or a byte string literal.
+/// Only ASCII characters are allowed, and '\x' escapes may go up to 0xFF.
+pub fn unescape_bytes(input: &str) -> Result<Vec<u8>, UnescapeError> {
+    let mut que = input.chars().collect::<VecDeque<char>>();
+    let mut res: Vec<u8> = Vec::new();
+
+    while let Some(c) = que.pop_front() {
+        if c != '\\' {
+            if !c.is_ascii() {
+                return Err(NonAsciiInByte);
+            }
+
+            res.push(c as u8);
+            continue;
+        }
+
+        match que.pop_front() {
+            None => return Err(OnlyOneSlashError),
+            Some('b') => res.push(0x08),
+            Some('r') => res.push(b'\r'),
+            Some('n') => res.push(b'\n'),
+            Some('t') => res.push(b'\t'),
+            Some('\'') => res.push(b'\''),
+            Some('\\') => res.push(b'\\'),
+            Some('u') => return Err(UnicodeInByte),
+
+            Some('x') => {
+                let high = que.pop_front().ok_or(TooShortEscape)?;
+                let high = high.to_digit(16).ok_or(InvalidCharInHex)?;
+
+                let low = que.pop_front().ok_or(TooShortEscape)?;
+                let low = low.to_digit(16).ok_or(InvalidCharInHex)?;
+
+                res.push((high * 16 + low) as u8);
+            }
+
+            _ => return Err(IllegalEscape),
+        }
+    }
+
+    Ok(res)
+}
+
+/// Turn a string back into a Fire string literal, the inverse of lexing it.
+///
+/// A raw string is chosen when it is shorter, like `r"(say "hi")"` instead of
+/// `"say \x22hi\x22"`.
+pub fn escape(input: &str) -> String {
+    let res = format!("\"{}\"", escape_str(input));
+
+    match escape_raw(input) {
+        Some(raw) if raw.len() < res.len() => raw,
+        _ => res,
+    }
+}
+
+/// Escape the text of a string literal, without the quotes, like a part of
+/// an interpolated string.
+pub fn escape_str(input: &str) -> String {
+    let mut res = String::new();
+
+    for c in input.chars() {
+        match c {
+            // a '"' always closes the literal, even after a '\'.
+            '"' => res.push_str("\\x22"),
+            '{' => res.push_str("{{"),
+            '}' => res.push_str("}}"),
+            c => push_escaped(&mut res, c),
+        }
+    }
+
+    res
+}
+
+/// Turn a char back into a Fire character literal.
+pub fn escape_char(c: char) -> String {
+    let mut res = String::from("'");
+
+    match c {
+        '\'' => res.push_str("\\'"),
+        c => push_escaped(&mut res, c),
+    }
+
+    res.push('\'');
+    res
+}
+
+fn push_escaped(res: &mut String, c: char) {
+    match c {
+        '\\' => res.push_str("\\\\"),
+        '\n' => res.push_str("\\n"),
+        '\r' => res.push_str("\\r"),
+        '\t' => res.push_str("\\t"),
+        c if !is_printable(c) => res.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => res.push(c),
+    }
+}
+
+/// `r"delim(...)delim"` with the shortest delimiter which does not appear in `input`, or `None`
+/// if a raw string cannot hold it.
+fn escape_raw(input: &str) -> Option<String> {
+    // "\r\n" would be read as "\n", and '\0' as the end of file.
+    if !input
+        .chars()
+        .all(|c| is_printable(c) || c == '\n' || c == '\t')
+    {
+        return None;
+    }
+
+    (0..=super::lexer::MAX_RAW_STR_DELIMITER)
+        .map(|n| "#".repeat(n))
+        .find(|delim| !input.contains(&format!("){delim}\"")))
+        .map(|delim| format!("r\"{delim}({input}){delim}\""))
+}
+
+/// The control chars and the invisible ones are written as escape sequences.
+fn is_printable(c: char) -> bool {
+    if c.is_ascii() {
+        return !c.is_ascii_control();
+    }
+
+    // a combining mark is only escaped by `escape_debug` at the start of a string.
+    !format!(" {c}").escape_debug().any(|x| x == '\\')
+}
@@ -0,0 +1,233 @@
+use crate::compiler::firelang_lexer::symbol::Symbol;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use super::node::*;
+use super::token::BinaryOp;
+use super::visit::MutVisitor;
+use std::cell::RefCell;
+
+/// The version of the format, it changes when a file written by the previous
+/// version cannot be read.
+pub const VERSION: u32 = 1;
+
+thread_local! {
+    /// The expressions of the module being written or read, the children are
+    /// written in place of their `ExprId`.
+    static EXPRS: RefCell<Exprs> = const { RefCell::new(Exprs::new()) };
+}
+
+/// Write the module as JSON with its version, like
+/// `{"version":1,"kind":"Module","items":[..]}`.
+///
+/// The nodes are objects with a "kind", like
+/// `{"kind":"Binary","lhs":{..},"op":"+","rhs":{..}}`, and the literals are
+/// `{"kind":"Literal","type":"int64","value":1}`. The 128-bit integers and
+/// the integers without suffix, whose type is "int", are strings, since most
+/// readers of JSON keep the numbers in a `f64`.
+pub fn to_string(module: &Module) -> String {
+    #[derive(serde::Serialize)]
+    struct File<'m> {
+        version: u32,
+        #[serde(flatten)]
+        module: &'m Module,
+    }
+
+    // the children are looked up in a copy of the arena while they are written.
+    EXPRS.with(|x| *x.borrow_mut() = module.exprs.clone());
+
+    let text = serde_json::to_string(&File {
+        version: VERSION,
+        module,
+    })
+    .unwrap();
+
+    EXPRS.with(|x| x.take());
+    text
+}
+
+/// Read a module written by `to_string`.
+///
+/// The ids are not written, the tree read back is numbered like the parser
+/// numbers it, the children before their parent. So a module without syntax
+/// errors gets its ids again, but the ids which the parser gave to the nodes
+/// of the failed statements are not kept.
+pub fn from_str(text: &str) -> Result<Module, String> {
+    #[derive(serde::Deserialize)]
+    struct Version {
+        version: u32,
+    }
+
+    let Version { version } = serde_json::from_str(text).map_err(|e| e.to_string())?;
+
+    if version != VERSION {
+        return Err(format!(
+            "unsupported version {version}, the version of the format is {VERSION}"
+        ));
+    }
+
+    EXPRS.with(|x| x.take());
+    let module = serde_json::from_str::<Module>(text);
+    let exprs = EXPRS.with(|x| x.take());
+
+    let mut module = module.map_err(|e| e.to_string())?;
+    module.exprs = exprs;
+    NodeIds::default().visit_module(&mut module);
+    Ok(module)
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d).map(|x| Symbol::intern(&x))
+    }
+}
+
+/// "+", like in the source code.
+impl Serialize for BinaryOp {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BinaryOp {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let x = String::deserialize(d)?;
+        BinaryOp::try_from(x.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.kind.serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Expression::deserialize(d).map(Expr::from)
+    }
+}
+
+impl Serialize for ExprId {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        EXPRS.with(|x| x.borrow()[*self].serialize(s))
+    }
+}
+
+/// The children are read first, so they are put in the arena before their
+/// parent like the parser puts them.
+impl<'de> Deserialize<'de> for ExprId {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let expr = Expr::deserialize(d)?;
+        Ok(EXPRS.with(|x| x.borrow_mut().alloc(expr)))
+    }
+}
+
+impl Serialize for Stmt {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.kind.serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Stmt {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Statement::deserialize(d).map(Stmt::from)
+    }
+}
+
+/// Write the value of a variant with the field `$field`, the variants with a
+/// "kind" are objects, so their value must be an object too.
+macro_rules! field {
+    ($name:ident, $field:ident: $ty:ty) => {
+        pub(super) mod $name {
+            use super::*;
+
+            #[derive(serde::Serialize)]
+            struct Ref<'a> {
+                $field: &'a $ty,
+            }
+
+            #[derive(serde::Deserialize)]
+            struct Owned {
+                $field: $ty,
+            }
+
+            pub fn serialize<S: Serializer>(x: &$ty, s: S) -> Result<S::Ok, S::Error> {
+                Ref { $field: x }.serialize(s)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<$ty, D::Error> {
+                Owned::deserialize(d).map(|x| x.$field)
+            }
+        }
+    };
+}
+
+field!(name, name: Symbol);
+field!(stmts, stmts: Block);
+field!(value, value: ExprId);
+
+/// The parameters as `{"name":"a","behaviour":"<-","type":"int32"}`.
+pub(super) mod params {
+    use super::*;
+
+    #[derive(serde::Serialize)]
+    struct ParamRef<'a> {
+        name: &'a Symbol,
+        behaviour: &'a Behaviour,
+        #[serde(rename = "type")]
+        ty: &'a Symbol,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Param {
+        name: Symbol,
+        behaviour: Behaviour,
+        #[serde(rename = "type")]
+        ty: Symbol,
+    }
+
+    pub fn serialize<S: Serializer>(
+        params: &[(Symbol, Behaviour, Symbol)],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_seq(params.iter().map(|(name, behaviour, ty)| ParamRef {
+            name,
+            behaviour,
+            ty,
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Vec<(Symbol, Behaviour, Symbol)>, D::Error> {
+        let params = Vec::<Param>::deserialize(d)?;
+        Ok(params
+            .into_iter()
+            .map(|x| (x.name, x.behaviour, x.ty))
+            .collect())
+    }
+}
+
+/// The 128-bit integers as strings.
+pub(super) mod text {
+    use super::*;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(x: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(x)
+    }
+
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+        let x = String::deserialize(d)?;
+        x.parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid value `{x}`")))
+    }
+}
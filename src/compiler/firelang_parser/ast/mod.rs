@@ -1,3 +1,7 @@
+pub mod json;
 pub mod node;
 pub(super) mod node_impl;
+pub mod printer;
+pub mod sexp;
 pub mod token;
+pub mod visit;
@@ -1,66 +1,164 @@
+use crate::compiler::firelang_lexer::lexer::Span;
+use crate::compiler::firelang_lexer::symbol::Symbol;
 use crate::compiler::firelang_parser::ast::token::Literal;
 
 use super::token::BinaryOp;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialOrd, PartialEq)]
+/// The identity of a node, the key of the side tables of the passes, like the
+/// types of the expressions.
+///
+/// The ids are given in the order the nodes are made, so they are dense and a
+/// table can also be a vector indexed by `NodeId::index`.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct NodeId(pub u32);
+
+/// Gives out the ids of the nodes of a tree, each one once.
+#[derive(Debug, Clone, Default)]
+pub struct NodeIds {
+    pub(super) next: u32,
+}
+
+/// Where an expression is in the `Exprs` of its tree.
+///
+/// The children of the expressions and the values of the statements are
+/// these indices, not boxes, so the expressions of a tree are in one vector.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct ExprId(pub u32);
+
+/// The expressions of a tree, allocated one after another and indexed by
+/// `ExprId`.
+///
+/// A tree is made and dropped at once, so the expressions are never freed
+/// one by one, and the parser doesn't allocate a box for each of them.
+#[derive(Debug, Clone, Default)]
+pub struct Exprs {
+    pub(super) exprs: Vec<Expr>,
+}
+
+/// An expression and its id.
+///
+/// Two nodes are equal if their contents are, the ids are not compared. The
+/// children are compared by their `ExprId`, the `Module`s compare the whole
+/// trees, so a tree printed and parsed again is the same tree.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub id: NodeId,
+    pub kind: Expression,
+}
+
+/// A statement and its id.
+#[derive(Debug)]
+pub struct Stmt {
+    pub id: NodeId,
+    pub kind: Statement,
+}
+
+/// The expressions and the statements are written to JSON with their
+/// "kind", see `json::to_string`.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
 pub enum Expression {
     Literal(Literal),
 
     Binary {
-        lhs: Box<Expression>,
+        lhs: ExprId,
         op: BinaryOp,
-        rhs: Box<Expression>,
+        rhs: ExprId,
     },
 
-    Ident(String),
+    Ident(#[serde(with = "super::json::name")] Symbol),
 
     FuncCall {
-        ident: String,
-        args: Vec<Expression>,
+        ident: Symbol,
+        args: Vec<ExprId>,
+    },
+
+    // "a + b = {a + b}"
+    Interpolated {
+        parts: Vec<InterpolatedPart>,
     },
 
     None,
+
+    /// The placeholder of an expression with a syntax error.
+    Error,
 }
 
-#[derive(Debug, PartialOrd, PartialEq)]
+/// The text is a string, the embedded expressions are objects.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InterpolatedPart {
+    Str(String),
+    Expr(ExprId),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Block {
-    pub block: Vec<Statement>,
+    pub block: Vec<Stmt>,
 }
 
-#[derive(Debug, PartialOrd, PartialEq)]
+#[derive(Debug, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub enum Behaviour {
+    #[serde(rename = "=")]
     Copy,
+    #[serde(rename = "<-")]
     Move,
+    #[serde(rename = "->")]
     Ref,
 }
 
-#[derive(Debug, PartialOrd, PartialEq)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
 pub enum Statement {
-    Block(Block),
+    Block(#[serde(with = "super::json::stmts")] Block),
 
     FuncDecl {
-        ident: String,
+        doc: Option<String>,
+        ident: Symbol,
         // param := <ident> ("=" | "<-" | "->") <type>
-        params: Vec<(String, Behaviour, String)>,
+        #[serde(with = "super::json::params")]
+        params: Vec<(Symbol, Behaviour, Symbol)>,
         body: Block,
     },
 
     VariableDecl {
-        ident: String,
-        ty: String,
+        doc: Option<String>,
+        ident: Symbol,
+        /// Empty if the type is not written.
+        #[serde(rename = "type")]
+        ty: Symbol,
         mutable: bool,
         behaviour: Behaviour,
-        value: Expression,
+        value: ExprId,
     },
 
-    Return(Expression),
+    Return(#[serde(with = "super::json::value")] ExprId),
 
     If {
-        cond: Expression,
+        cond: ExprId,
         block: Block,
         // else
+        #[serde(rename = "else")]
         els: Option<Block>,
     },
 
-    Eof,
+    /// The placeholder of a statement with a syntax error.
+    Error,
+}
+
+/// The root of the tree, a whole source file.
+///
+/// Two modules are equal if their trees are, wherever the expressions are in
+/// their arenas.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub struct Module {
+    pub items: Vec<Stmt>,
+    /// The expressions of the items.
+    #[serde(skip)]
+    pub exprs: Exprs,
+    #[serde(skip)]
+    pub span: Span,
 }
@@ -1,161 +1,166 @@
 use crate::compiler::firelang_lexer::lexer::LiteralKind::*;
-use crate::compiler::firelang_lexer::lexer::RawStrError::*;
-use crate::compiler::firelang_lexer::lexer::{Token, TokenKind};
+use crate::compiler::firelang_lexer::lexer::{int_digits, int_range, Token, TokenKind};
+use crate::compiler::firelang_lexer::symbol::Symbol;
 
 use crate::compiler::firelang_lexer::unescape::UnescapeError;
-use crate::compiler::firelang_lexer::unescape::UnescapeError::*;
+use crate::compiler::firelang_lexer::unescape::{escape, escape_char, unescape_bytes};
 
 use super::node::*;
 use super::token;
+use super::visit::{walk_expr_mut, walk_stmt_mut, MutVisitor};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
 
+/// The literal of a token, an invalid one is `Literal::Unexpected`, which the
+/// lexer has reported.
 pub fn make_lit(tok: Token) -> Expression {
     fn check_escape_err(err: &UnescapeError) -> token::Literal {
-        match err {
-            OnlyOneSlashError => {
-                token::Literal::Unexpected(r"only one '\' in character literal".into())
-            }
-
-            IllegalEscape => token::Literal::Unexpected(
-                "there are some illegal characters in the escape sequence".into(),
-            ),
-
-            EmptyUnicode => {
-                token::Literal::Unexpected("unexpected empty unicode escape sequence".into())
-            }
-
-            UnclosedUnicode => {
-                token::Literal::Unexpected("there is not a '}' to close the escape sequence".into())
-            }
-
-            IllegalUnicode => token::Literal::Unexpected(
-                "there is an illegal unicode escape sequence in the character literal".into(),
-            ),
-
-            TooLongUnicode => {
-                token::Literal::Unexpected("too long value in the unicode escape sequence".into())
-            }
-
-            ValueOutOfUnicode => token::Literal::Unexpected(
-                "value out of range: unicode value cannot be more than 10FFFF".into(),
-            ),
-
-            LoneSurrogate => token::Literal::Unexpected(
-                "there is a lone surrogate codepoint in the unicode character".into(),
-            ),
-
-            InvalidCharInUnicode => token::Literal::Unexpected(
-                "there is an invalid character in the unicode escape sequence".into(),
-            ),
-
-            TooShortEscape => token::Literal::Unexpected(
-                "too short escape sequence in ascii escape character".into(),
-            ),
-
-            InvalidCharInHex => token::Literal::Unexpected(
-                "there is an invalid character in the ascii escape sequence".into(),
-            ),
-
-            ValueOutOfHex => token::Literal::Unexpected(
-                "value out of range: ascii escape value cannot be more than 0x7F".into(),
-            ),
-        }
+        token::Literal::Unexpected(err.message().into())
     }
 
     let val: token::Literal = match &tok.kind {
         TokenKind::Literal { kind, suffix } => match (kind, suffix.as_str()) {
-            (Int { .. }, suffix) => match suffix {
-                "" | "i32" => {
-                    token::Literal::Int(tok.content.replace("i32", "").parse::<i32>().unwrap())
-                }
+            (Int { base, .. }, suffix) => {
+                let value = u128::from_str_radix(int_digits(&tok.content, base), base.radix());
+
+                match (value, int_range(suffix)) {
+                    (Ok(x), Some((_, max))) if x <= max => match suffix {
+                        "b" => token::Literal::Byte(x as i8),
+                        "u8" => token::Literal::UByte(x as u8),
+                        "i16" => token::Literal::Int16(x as i16),
+                        "u16" => token::Literal::UInt16(x as u16),
+                        "u32" => token::Literal::UInt(x as u32),
+                        "i64" => token::Literal::Int64(x as i64),
+                        "u64" => token::Literal::UInt64(x as u64),
+                        "i128" => token::Literal::Int128(x as i128),
+                        "u128" => token::Literal::UInt128(x),
+                        "i32" => token::Literal::Int(x as i32),
+                        _ => token::Literal::UnsuffixedInt(x),
+                    },
+
+                    (Ok(_), Some((ty, _))) => {
+                        token::Literal::Unexpected(format!("literal out of range for `{ty}`"))
+                    }
 
-                "u32" => {
-                    token::Literal::UInt(tok.content.replace("u32", "").parse::<u32>().unwrap())
+                    (Err(_), Some(_)) => {
+                        token::Literal::Unexpected("invalid integer literal".into())
+                    }
+
+                    (_, None) => token::Literal::Unexpected("invalid suffix of literal".into()),
                 }
+            }
 
-                "b" => token::Literal::Byte(tok.content.replace('b', "").parse::<i8>().unwrap()),
+            (Float { dangling }, suffix) => {
+                // like `0x1.5`, which the lexer has reported.
+                let invalid = |_| token::Literal::Unexpected("invalid float literal".into());
 
-                "u8" => token::Literal::UByte(tok.content.replace("u8", "").parse::<u8>().unwrap()),
+                if *dangling {
+                    token::Literal::Unexpected("dangling float number".into())
+                } else {
+                    let lit = match suffix {
+                        "f32" => tok
+                            .content
+                            .replace("f32", "")
+                            .parse::<f32>()
+                            .map_or_else(invalid, token::Literal::Float),
+
+                        "" => tok
+                            .content
+                            .parse::<f64>()
+                            .map_or_else(invalid, token::Literal::UnsuffixedFloat),
+
+                        "f64" => tok
+                            .content
+                            .replace("f64", "")
+                            .parse::<f64>()
+                            .map_or_else(invalid, token::Literal::Float64),
 
-                "i16" => {
-                    token::Literal::Int16(tok.content.replace("i16", "").parse::<i16>().unwrap())
-                }
+                        _ => token::Literal::Unexpected("invalid suffix of literal".into()),
+                    };
 
-                "u16" => {
-                    token::Literal::UInt16(tok.content.replace("u16", "").parse::<u16>().unwrap())
-                }
+                    // like `1e999`, the values are finite.
+                    match lit {
+                        token::Literal::Float(x) if x.is_infinite() => {
+                            token::Literal::Unexpected("literal out of range for `float32`".into())
+                        }
 
-                "i64" => {
-                    token::Literal::Int64(tok.content.replace("i64", "").parse::<i64>().unwrap())
-                }
+                        token::Literal::Float64(x) | token::Literal::UnsuffixedFloat(x)
+                            if x.is_infinite() =>
+                        {
+                            token::Literal::Unexpected("literal out of range for `float64`".into())
+                        }
 
-                "u64" => {
-                    token::Literal::UInt64(tok.content.replace("u64", "").parse::<u64>().unwrap())
+                        x => x,
+                    }
                 }
+            }
 
-                "i128" => {
-                    token::Literal::Int128(tok.content.replace("i128", "").parse::<i128>().unwrap())
-                }
+            (Char { unclose, err, len }, _) => {
+                if *unclose {
+                    token::Literal::Unexpected("unclose character literal".into())
+                } else {
+                    match (err, len) {
+                        (Some(err), _) => check_escape_err(err),
 
-                "u128" => token::Literal::UInt128(
-                    tok.content.replace("u128", "").parse::<u128>().unwrap(),
-                ),
+                        (None, Some(len)) => token::Literal::Unexpected(len.message("character")),
 
-                _ => token::Literal::Unexpected("invalid suffix of literal".into()),
-            },
+                        (None, None) => token::Literal::Char(tok.content.chars().next().unwrap()),
+                    }
+                }
+            }
 
-            (Float { dangling }, suffix) => {
-                if *dangling {
-                    token::Literal::Unexpected("dangling float number".into())
+            (Byte { unclose, err, len }, _) => {
+                if *unclose {
+                    token::Literal::Unexpected("unclose byte literal".into())
                 } else {
-                    match suffix {
-                        "f32" | "" => token::Literal::Float(
-                            tok.content.replace("f32", "").parse::<f32>().unwrap(),
-                        ),
+                    match (err, len) {
+                        (Some(err), _) => check_escape_err(err),
 
-                        "f64" => token::Literal::Float64(
-                            tok.content.replace("f64", "").parse::<f64>().unwrap(),
-                        ),
+                        (None, Some(len)) => token::Literal::Unexpected(len.message("byte")),
 
-                        _ => token::Literal::Unexpected("invalid suffix of literal".into()),
+                        (None, None) => {
+                            token::Literal::UByte(unescape_bytes(&tok.content).unwrap()[0])
+                        }
                     }
                 }
             }
 
-            (Char { unclose, err }, _) => {
+            (ByteStr { unclose, err }, _) => {
                 if *unclose {
-                    token::Literal::Unexpected("unclose character literal".into())
+                    token::Literal::Unexpected("unclose byte string literal".into())
                 } else {
                     match err {
                         Some(err) => check_escape_err(err),
 
-                        None => token::Literal::Char(tok.content),
+                        None => token::Literal::ByteStr(unescape_bytes(&tok.content).unwrap()),
                     }
                 }
             }
 
             (Str { unclose, err }, _) => {
                 if *unclose {
-                    token::Literal::Unexpected("unclose character literal".into())
+                    token::Literal::Unexpected("unclose string literal".into())
                 } else {
                     match err {
                         Some(err) => check_escape_err(err),
 
-                        None => token::Literal::Str(tok.content),
+                        None => token::Literal::Str(tok.content.into_owned()),
                     }
                 }
             }
 
-            (RawStr { err }, _) => match err {
-                Some(err) => match err {
-                    UncloseString => {
-                        token::Literal::Unexpected("the string literal is unclosed".into())
-                    }
+            (InterpolatedStr { err, .. }, _) => match err {
+                Some(err) => token::Literal::Unexpected(err.message().into()),
 
-                    UncloseParen => token::Literal::Unexpected(
-                        "missed a parentheses in the raw string literal".into(),
-                    ),
-                },
-                None => token::Literal::Str(tok.content),
+                None => token::Literal::Unexpected(
+                    "an interpolated string literal cannot be used as a constant".into(),
+                ),
+            },
+
+            (RawStr { err }, _) => match err {
+                Some(err) => token::Literal::Unexpected(err.message()),
+                None => token::Literal::Str(tok.content.into_owned()),
             },
         },
 
@@ -165,6 +170,321 @@ pub fn make_lit(tok: Token) -> Expression {
     Expression::Literal(val)
 }
 
-pub fn make_ident(s: String) -> Expression {
+pub fn make_ident(s: Symbol) -> Expression {
     Expression::Ident(s)
 }
+
+impl NodeId {
+    /// The id of the nodes made after the parsing, which no side table knows.
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl NodeIds {
+    /// Start after the ids which have been given out already, e.g. by the
+    /// parser of the rest of the document.
+    pub fn starting_at(next: u32) -> NodeIds {
+        NodeIds { next }
+    }
+
+    pub fn next_id(&mut self) -> NodeId {
+        self.next += 1;
+        NodeId(self.next - 1)
+    }
+
+    /// How many ids have been given out, the length of a vector indexed by them.
+    pub fn count(&self) -> usize {
+        self.next as usize
+    }
+}
+
+/// Give fresh ids to the nodes of a tree which the parser has not made, in
+/// the order the parser gives them, the children before their parent.
+impl MutVisitor for NodeIds {
+    fn visit_stmt(&mut self, exprs: &mut Exprs, stmt: &mut Stmt) {
+        walk_stmt_mut(self, exprs, stmt);
+        stmt.id = self.next_id();
+    }
+
+    fn visit_expr(&mut self, exprs: &mut Exprs, id: ExprId) {
+        walk_expr_mut(self, exprs, id);
+        exprs[id].id = self.next_id();
+    }
+}
+
+impl ExprId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Exprs {
+    pub const fn new() -> Exprs {
+        Exprs { exprs: Vec::new() }
+    }
+
+    /// The arena of a tree without expressions, for the passes which are
+    /// given their module later.
+    pub fn empty() -> &'static Exprs {
+        static EMPTY: Exprs = Exprs::new();
+        &EMPTY
+    }
+
+    /// Put the expression after the others.
+    pub fn alloc(&mut self, expr: Expr) -> ExprId {
+        self.exprs.push(expr);
+        ExprId(self.exprs.len() as u32 - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.exprs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exprs.is_empty()
+    }
+}
+
+impl Index<ExprId> for Exprs {
+    type Output = Expr;
+
+    fn index(&self, id: ExprId) -> &Expr {
+        &self.exprs[id.index()]
+    }
+}
+
+impl IndexMut<ExprId> for Exprs {
+    fn index_mut(&mut self, id: ExprId) -> &mut Expr {
+        &mut self.exprs[id.index()]
+    }
+}
+
+impl Expr {
+    pub fn new(id: NodeId, kind: Expression) -> Expr {
+        Expr { id, kind }
+    }
+}
+
+impl Stmt {
+    pub fn new(id: NodeId, kind: Statement) -> Stmt {
+        Stmt { id, kind }
+    }
+}
+
+impl From<Expression> for Expr {
+    fn from(kind: Expression) -> Expr {
+        Expr::new(NodeId::DUMMY, kind)
+    }
+}
+
+impl From<Statement> for Stmt {
+    fn from(kind: Statement) -> Stmt {
+        Stmt::new(NodeId::DUMMY, kind)
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl PartialOrd for Expr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.kind.partial_cmp(&other.kind)
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        let same = Same {
+            a: &self.exprs,
+            b: &other.exprs,
+        };
+
+        same.stmts(&self.items, &other.items)
+    }
+}
+
+/// Whether the statement `a`, whose expressions are in `exprs`, is the same as
+/// the statement `b`, whose expressions are in `other`.
+pub(crate) fn same_stmt(exprs: &Exprs, a: &Stmt, other: &Exprs, b: &Stmt) -> bool {
+    Same { a: exprs, b: other }.stmt(a, b)
+}
+
+/// Compare two trees, the expressions of each are in its own arena.
+struct Same<'a> {
+    a: &'a Exprs,
+    b: &'a Exprs,
+}
+
+impl Same<'_> {
+    fn stmts(&self, a: &[Stmt], b: &[Stmt]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.stmt(a, b))
+    }
+
+    fn block(&self, a: &Block, b: &Block) -> bool {
+        self.stmts(&a.block, &b.block)
+    }
+
+    fn stmt(&self, a: &Stmt, b: &Stmt) -> bool {
+        match (&a.kind, &b.kind) {
+            (Statement::Block(a), Statement::Block(b)) => self.block(a, b),
+
+            (
+                Statement::FuncDecl {
+                    doc,
+                    ident,
+                    params,
+                    body,
+                },
+                Statement::FuncDecl {
+                    doc: doc2,
+                    ident: ident2,
+                    params: params2,
+                    body: body2,
+                },
+            ) => doc == doc2 && ident == ident2 && params == params2 && self.block(body, body2),
+
+            (
+                Statement::VariableDecl {
+                    doc,
+                    ident,
+                    ty,
+                    mutable,
+                    behaviour,
+                    value,
+                },
+                Statement::VariableDecl {
+                    doc: doc2,
+                    ident: ident2,
+                    ty: ty2,
+                    mutable: mutable2,
+                    behaviour: behaviour2,
+                    value: value2,
+                },
+            ) => {
+                doc == doc2
+                    && ident == ident2
+                    && ty == ty2
+                    && mutable == mutable2
+                    && behaviour == behaviour2
+                    && self.expr(*value, *value2)
+            }
+
+            (Statement::Return(a), Statement::Return(b)) => self.expr(*a, *b),
+
+            (
+                Statement::If { cond, block, els },
+                Statement::If {
+                    cond: cond2,
+                    block: block2,
+                    els: els2,
+                },
+            ) => {
+                self.expr(*cond, *cond2)
+                    && self.block(block, block2)
+                    && match (els, els2) {
+                        (Some(a), Some(b)) => self.block(a, b),
+                        (a, b) => a.is_none() && b.is_none(),
+                    }
+            }
+
+            (Statement::Error, Statement::Error) => true,
+
+            _ => false,
+        }
+    }
+
+    fn expr(&self, a: ExprId, b: ExprId) -> bool {
+        match (&self.a[a].kind, &self.b[b].kind) {
+            (
+                Expression::Binary { lhs, op, rhs },
+                Expression::Binary {
+                    lhs: lhs2,
+                    op: op2,
+                    rhs: rhs2,
+                },
+            ) => op == op2 && self.expr(*lhs, *lhs2) && self.expr(*rhs, *rhs2),
+
+            (
+                Expression::FuncCall { ident, args },
+                Expression::FuncCall {
+                    ident: ident2,
+                    args: args2,
+                },
+            ) => {
+                ident == ident2
+                    && args.len() == args2.len()
+                    && args.iter().zip(args2).all(|(a, b)| self.expr(*a, *b))
+            }
+
+            (Expression::Interpolated { parts }, Expression::Interpolated { parts: parts2 }) => {
+                parts.len() == parts2.len()
+                    && parts.iter().zip(parts2).all(|x| match x {
+                        (InterpolatedPart::Str(a), InterpolatedPart::Str(b)) => a == b,
+                        (InterpolatedPart::Expr(a), InterpolatedPart::Expr(b)) => self.expr(*a, *b),
+                        _ => false,
+                    })
+            }
+
+            // the others have no children.
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl Display for Behaviour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Behaviour::Copy => f.write_str("="),
+            Behaviour::Move => f.write_str("<-"),
+            Behaviour::Ref => f.write_str("->"),
+        }
+    }
+}
+
+/// The literal in the source code, the suffix is written unless it has none.
+impl Display for token::Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            token::Literal::Byte(x) => write!(f, "{x}_b"),
+            token::Literal::Int16(x) => write!(f, "{x}_i16"),
+            token::Literal::Int(x) => write!(f, "{x}_i32"),
+            token::Literal::Int64(x) => write!(f, "{x}_i64"),
+            token::Literal::Int128(x) => write!(f, "{x}_i128"),
+            token::Literal::UByte(x) => write!(f, "{x}_u8"),
+            token::Literal::UInt16(x) => write!(f, "{x}_u16"),
+            token::Literal::UInt(x) => write!(f, "{x}_u32"),
+            token::Literal::UInt64(x) => write!(f, "{x}_u64"),
+            token::Literal::UInt128(x) => write!(f, "{x}_u128"),
+            token::Literal::UnsuffixedInt(x) => write!(f, "{x}"),
+            // `Debug` keeps the ".0" of the whole numbers.
+            token::Literal::Float(x) => write!(f, "{x:?}_f32"),
+            token::Literal::Float64(x) => write!(f, "{x:?}_f64"),
+            token::Literal::UnsuffixedFloat(x) => write!(f, "{x:?}"),
+            token::Literal::Char(x) => f.write_str(&escape_char(*x)),
+            token::Literal::Boolean(x) => write!(f, "{x}"),
+            token::Literal::Str(x) => f.write_str(&escape(x)),
+
+            token::Literal::ByteStr(x) => {
+                f.write_str("b\"")?;
+
+                for &b in x {
+                    match b {
+                        b'"' | b'\\' => write!(f, "\\x{b:02x}")?,
+                        b' '..=b'~' => write!(f, "{}", b as char)?,
+                        _ => write!(f, "\\x{b:02x}")?,
+                    }
+                }
+
+                f.write_str("\"")
+            }
+
+            token::Literal::Unexpected(x) => write!(f, "/* {x} */"),
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use crate::compiler::firelang_lexer::lexer::Span;
+use crate::compiler::firelang_lexer::unescape::escape_str;
+use crate::compiler::firelang_parser::parser::{Layout, PRECEDENCE};
+use unicode_xid::UnicodeXID;
+
+use super::node::*;
+use super::token::BinaryOp;
+
+/// One level of indentation.
+const INDENT: &str = "    ";
+
+/// Printer Struct
+/// Print the tree back to source code in the canonical style.
+///
+/// The comments and the literals are copied from the source by the `Layout`
+/// of the parser, so the hex numbers and the raw strings stay as they are
+/// written. A tree without a source is printed from the values.
+pub struct Printer<'s> {
+    src: &'s str,
+    layout: &'s Layout,
+    /// The expressions of the module being printed.
+    exprs: &'s Exprs,
+    /// The next comment, statement and literal in the layout.
+    comment: usize,
+    stmt: usize,
+    literal: usize,
+    /// The byte offset after the last statement or comment printed, the blank
+    /// lines after it are kept.
+    last: usize,
+    depth: usize,
+    out: String,
+}
+
+impl<'s> Printer<'s> {
+    pub fn new(src: &'s str, layout: &'s Layout) -> Printer<'s> {
+        Printer {
+            src,
+            layout,
+            exprs: Exprs::empty(),
+            comment: 0,
+            stmt: 0,
+            literal: 0,
+            last: 0,
+            depth: 0,
+            out: String::new(),
+        }
+    }
+
+    pub fn print_module(mut self, module: &'s Module) -> String {
+        self.exprs = &module.exprs;
+        self.stmts(&module.items);
+        self.comments_before(usize::MAX);
+        self.out
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        let mut prev = None;
+
+        for x in stmts {
+            self.item(x, prev);
+            prev = Some(x);
+        }
+    }
+
+    /// Print a statement on its own lines, with the comments before and after it.
+    fn item(&mut self, stmt: &Stmt, prev: Option<&Stmt>) {
+        let span = self.layout.stmts.get(self.stmt).copied();
+        self.stmt += 1;
+
+        // the functions are set apart, with their doc comments.
+        if prev.is_some_and(is_func) || (prev.is_some() && is_func(stmt)) {
+            self.blank_line();
+        }
+
+        if let Some(span) = span {
+            self.comments_before(span.start);
+
+            if self.gap(span.start) {
+                self.blank_line();
+            }
+        }
+
+        self.indent();
+
+        match span {
+            Some(span) if self.has_inline_comment(stmt, span) => self.stmt_as_written(stmt, span),
+            _ => self.stmt(stmt, span),
+        }
+
+        if let Some(span) = span {
+            self.last = self.last.max(span.end);
+        }
+
+        // a comment on the same line stays there.
+        while let Some(&c) = self.layout.comments.get(self.comment) {
+            if c.start < self.last || self.src[self.last..c.start].contains('\n') {
+                break;
+            }
+
+            self.comment += 1;
+            self.out.push(' ');
+            self.out.push_str(&self.comment_text(c));
+            self.last = c.end;
+        }
+
+        self.out.push('\n');
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, span: Option<Span>) {
+        let end = span.map(|x| x.end);
+
+        match &stmt.kind {
+            Statement::Block(x) => self.block(x, end),
+
+            Statement::FuncDecl {
+                doc,
+                ident,
+                params,
+                body,
+            } => {
+                if span.is_none() {
+                    self.doc(doc);
+                }
+
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|(name, bhv, ty)| format!("{name} {bhv} {ty}"))
+                    .collect();
+
+                self.out
+                    .push_str(&format!("fn {ident}({}) ", params.join(", ")));
+                self.block(body, end);
+            }
+
+            Statement::VariableDecl {
+                doc,
+                ident,
+                ty,
+                mutable,
+                behaviour,
+                value,
+            } => {
+                if span.is_none() {
+                    self.doc(doc);
+                }
+
+                self.out.push_str("let ");
+
+                if *mutable {
+                    self.out.push_str("mut ");
+                }
+
+                self.out.push_str(ident.as_str());
+
+                if !ty.is_empty() {
+                    self.out.push_str(&format!(": {ty}"));
+                }
+
+                self.out.push_str(&format!(" {behaviour} "));
+                self.expr(*value);
+                self.out.push(';');
+            }
+
+            Statement::Return(x) => {
+                self.out.push_str("return");
+
+                if self.exprs[*x].kind != Expression::None {
+                    self.out.push(' ');
+                    self.expr(*x);
+                }
+
+                self.out.push(';');
+            }
+
+            Statement::If { cond, block, els } => {
+                self.out.push_str("if ");
+                self.expr(*cond);
+                self.out.push(' ');
+                self.block(block, end);
+
+                if let Some(x) = els {
+                    self.out.push_str(" else ");
+                    self.block(x, end);
+                }
+            }
+
+            Statement::Error => self.out.push_str("<error>;"),
+        }
+    }
+
+    /// Whether a comment is inside the statement but not in its blocks, like
+    /// `let x = 1 /* one */ + 2;`, it would be moved by the printing.
+    fn has_inline_comment(&self, stmt: &Stmt, span: Span) -> bool {
+        let end = match stmt.kind {
+            Statement::FuncDecl { .. } => self.body_start(span),
+            Statement::Block(_) | Statement::If { .. } => span.start,
+            _ => span.end,
+        };
+
+        self.layout
+            .comments
+            .get(self.comment)
+            .is_some_and(|c| c.start > span.start && c.start < end)
+    }
+
+    /// Print the statement as it is written, but the body of a function,
+    /// which has no comment out of place.
+    fn stmt_as_written(&mut self, stmt: &Stmt, span: Span) {
+        match &stmt.kind {
+            Statement::FuncDecl { body, .. } => {
+                let start = self.body_start(span);
+                self.source(span.start, start);
+                self.out.truncate(self.out.trim_end().len());
+                self.out.push(' ');
+                self.block(body, Some(span.end));
+            }
+
+            _ => self.source(span.start, span.end),
+        }
+    }
+
+    /// Copy the source from `start` to `end`, with its comments and literals.
+    fn source(&mut self, start: usize, end: usize) {
+        self.out
+            .push_str(&self.src[start..end].replace("\r\n", "\n"));
+
+        while self
+            .layout
+            .comments
+            .get(self.comment)
+            .is_some_and(|x| x.start < end)
+        {
+            self.comment += 1;
+        }
+
+        while self
+            .layout
+            .literals
+            .get(self.literal)
+            .is_some_and(|x| x.start < end)
+        {
+            self.literal += 1;
+        }
+    }
+
+    /// Where the body of the function of `span` starts, at the first `{`
+    /// which is not in a comment.
+    fn body_start(&self, span: Span) -> usize {
+        let mut from = span.start;
+
+        for c in &self.layout.comments[self.comment..] {
+            if c.start >= span.end {
+                break;
+            }
+
+            if let Some(i) = self.src[from..c.start].find('{') {
+                return from + i;
+            }
+
+            from = from.max(c.end);
+        }
+
+        from + self.src[from..span.end]
+            .find('{')
+            .unwrap_or(span.end - from)
+    }
+
+    /// Print a block, `end` is where the statement which has it ends, the
+    /// comments before it are in the block.
+    fn block(&mut self, block: &Block, end: Option<usize>) {
+        let end = end.unwrap_or(0);
+        let comments = self.layout.comments.get(self.comment);
+
+        if block.block.is_empty() && comments.is_none_or(|x| x.start >= end) {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push_str("{\n");
+        self.depth += 1;
+        self.stmts(&block.block);
+        self.comments_before(end);
+        self.depth -= 1;
+        self.indent();
+        self.out.push('}');
+    }
+
+    fn expr(&mut self, id: ExprId) {
+        let exprs = self.exprs;
+
+        match &exprs[id].kind {
+            Expression::Literal(x) => {
+                let text = self.source_literal().unwrap_or_else(|| x.to_string());
+                self.out.push_str(&text);
+            }
+
+            Expression::Binary { lhs, op, rhs } => {
+                let p = precedence(op);
+
+                self.operand(*lhs, |x| x < p);
+                self.out.push_str(&format!(" {op} "));
+                // the operators are left-associative, `a - (b - c)` keeps its parentheses.
+                self.operand(*rhs, |x| x <= p);
+            }
+
+            Expression::Ident(x) => self.out.push_str(x.as_str()),
+
+            Expression::FuncCall { ident, args } => {
+                self.out.push_str(ident.as_str());
+                self.out.push('(');
+
+                for (i, x) in args.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+
+                    self.expr(*x);
+                }
+
+                self.out.push(')');
+            }
+
+            Expression::Interpolated { parts } => {
+                if let Some(text) = self.source_literal() {
+                    self.out.push_str(&text);
+                    return;
+                }
+
+                self.out.push('"');
+
+                for x in parts {
+                    match x {
+                        InterpolatedPart::Str(x) => self.out.push_str(&escape_str(x)),
+
+                        InterpolatedPart::Expr(x) => {
+                            self.out.push('{');
+                            self.expr(*x);
+                            self.out.push('}');
+                        }
+                    }
+                }
+
+                self.out.push('"');
+            }
+
+            Expression::None => (),
+
+            Expression::Error => self.out.push_str("<error>"),
+        }
+    }
+
+    /// Print an operand of a binary expression, in parentheses if `paren` is
+    /// true for the precedence of its operator.
+    fn operand(&mut self, id: ExprId, paren: impl Fn(i32) -> bool) {
+        let paren = match &self.exprs[id].kind {
+            Expression::Binary { op, .. } => paren(precedence(op)),
+            _ => false,
+        };
+
+        if paren {
+            self.out.push('(');
+        }
+
+        self.expr(id);
+
+        if paren {
+            self.out.push(')');
+        }
+    }
+
+    /// The source code of the next literal.
+    fn source_literal(&mut self) -> Option<String> {
+        let span = self.layout.literals.get(self.literal)?;
+        self.literal += 1;
+
+        // the span of a number stops before its suffix, like "_i64".
+        let suffix = self.src[span.end..]
+            .find(|x: char| !UnicodeXID::is_xid_continue(x))
+            .unwrap_or(self.src.len() - span.end);
+
+        Some(self.src[span.start..span.end + suffix].replace("\r\n", "\n"))
+    }
+
+    /// Print the comments which start before `end`, each on its own lines.
+    fn comments_before(&mut self, end: usize) {
+        while let Some(&c) = self.layout.comments.get(self.comment) {
+            if c.start >= end {
+                break;
+            }
+
+            self.comment += 1;
+
+            if self.gap(c.start) {
+                self.blank_line();
+            }
+
+            self.indent();
+            self.out.push_str(&self.comment_text(c));
+            self.out.push('\n');
+            self.last = self.last.max(c.end);
+        }
+    }
+
+    fn comment_text(&self, span: Span) -> String {
+        self.src[span.start..span.end]
+            .trim_end_matches('\r')
+            .replace("\r\n", "\n")
+    }
+
+    /// The doc comment of a tree without a source.
+    fn doc(&mut self, doc: &Option<String>) {
+        let Some(doc) = doc else {
+            return;
+        };
+
+        for line in doc.lines() {
+            self.out.push_str(format!("/// {line}").trim_end());
+            self.out.push('\n');
+            self.indent();
+        }
+    }
+
+    /// Whether there is a blank line between the last thing printed and `start`.
+    fn gap(&self, start: usize) -> bool {
+        start > self.last && self.src[self.last..start].matches('\n').count() > 1
+    }
+
+    /// Leave a blank line, but not at the start of the file or of a block.
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+}
+
+fn is_func(stmt: &Stmt) -> bool {
+    matches!(stmt.kind, Statement::FuncDecl { .. })
+}
+
+fn precedence(op: &BinaryOp) -> i32 {
+    PRECEDENCE.get(op).copied().unwrap_or(-1)
+}
@@ -0,0 +1,694 @@
+use crate::compiler::firelang_lexer::lexer::Span;
+use std::fmt::{Display, Formatter};
+
+use super::node::*;
+use super::token::{BinaryOp, Literal};
+use super::visit::MutVisitor;
+
+/// An S-expression, the compact form of the tree.
+///
+/// The names are atoms, the literals without suffix are numbers and the `str`
+/// ones are strings, like `(let x = (+ a 1))`. The other literals have their
+/// type, like `(int64 1)` or `(char "c")`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sexp {
+    Atom(String),
+    /// A string in double quotes, escaped the same as in JSON.
+    Str(String),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    pub fn parse(text: &str) -> Result<Sexp, String> {
+        let mut reader = Reader::new(text);
+        let value = sexp(&mut reader)?;
+
+        reader.skip_space();
+        if !reader.rest().is_empty() {
+            return Err(reader.error("end of input"));
+        }
+
+        Ok(value)
+    }
+
+    fn atom(text: &str) -> Sexp {
+        Sexp::Atom(text.into())
+    }
+
+    fn list(head: &str, rest: impl IntoIterator<Item = Sexp>) -> Sexp {
+        let mut xs = vec![Sexp::atom(head)];
+        xs.extend(rest);
+        Sexp::List(xs)
+    }
+
+    fn as_atom(&self) -> Result<&str, String> {
+        match self {
+            Sexp::Atom(x) => Ok(x),
+            _ => Err(format!("expected an atom, found `{self}`")),
+        }
+    }
+
+    fn as_list(&self) -> Result<&[Sexp], String> {
+        match self {
+            Sexp::List(x) => Ok(x),
+            _ => Err(format!("expected a list, found `{self}`")),
+        }
+    }
+
+    /// The rest of a list which starts with the atom `head`.
+    fn args(&self, head: &str) -> Result<&[Sexp], String> {
+        match self.as_list()? {
+            [Sexp::Atom(x), rest @ ..] if x == head => Ok(rest),
+            _ => Err(format!("expected `({head} ..)`, found `{self}`")),
+        }
+    }
+}
+
+impl Display for Sexp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sexp::Atom(x) => f.write_str(x),
+            Sexp::Str(x) => f.write_str(&serde_json::to_string(x).unwrap()),
+
+            Sexp::List(xs) => {
+                f.write_str("(")?;
+
+                for (i, x) in xs.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+
+                    write!(f, "{x}")?;
+                }
+
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Read the atoms and the strings of the S-expressions.
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(src: &'a str) -> Reader<'a> {
+        Reader { src, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.rest().chars().next() {
+            Some(c) => format!("expected {expected}, found `{c}` at offset {}", self.pos),
+            None => format!("expected {expected}, found end of input"),
+        }
+    }
+
+    /// Eat `s` after the spaces.
+    fn eat(&mut self, s: &str) -> bool {
+        self.skip_space();
+
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            return true;
+        }
+
+        false
+    }
+
+    /// Take the chars while `f` is true.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|x| !f(x)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// A string in double quotes, with the escapes of JSON.
+    fn string(&mut self) -> Result<String, String> {
+        if !self.rest().starts_with('"') {
+            return Err(self.error("a string"));
+        }
+
+        self.pos += 1;
+        let mut out = String::new();
+
+        loop {
+            let mut chars = self.rest().chars();
+            let Some(c) = chars.next() else {
+                return Err(self.error("`\"`"));
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(out),
+
+                '\\' => {
+                    let Some(e) = chars.next() else {
+                        return Err(self.error("an escape"));
+                    };
+                    self.pos += e.len_utf8();
+
+                    match e {
+                        '"' | '\\' | '/' => out.push(e),
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'n' => out.push('\n'),
+                        'r' => out.push('\r'),
+                        't' => out.push('\t'),
+
+                        'u' => {
+                            let mut c = self.hex4()?;
+
+                            // a char out of the BMP is a surrogate pair.
+                            if (0xD800..0xDC00).contains(&c) && self.rest().starts_with("\\u") {
+                                self.pos += 2;
+
+                                match self.hex4()? {
+                                    low @ 0xDC00..=0xDFFF => {
+                                        c = 0x10000 + ((c - 0xD800) << 10) + (low - 0xDC00)
+                                    }
+                                    _ => return Err("lone surrogate in string".into()),
+                                }
+                            }
+
+                            match char::from_u32(c) {
+                                Some(c) => out.push(c),
+                                None => return Err(format!("invalid char `\\u{c:x}` in string")),
+                            }
+                        }
+
+                        _ => return Err(format!("unknown escape `\\{e}` in string")),
+                    }
+                }
+
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex = self.rest().get(..4).unwrap_or("");
+
+        match u32::from_str_radix(hex, 16) {
+            Ok(x) if hex.len() == 4 => {
+                self.pos += 4;
+                Ok(x)
+            }
+            _ => Err(self.error("4 hex digits")),
+        }
+    }
+}
+
+/// Make a literal from the name of its type and the text of its value.
+fn make_literal(ty: &str, value: &str) -> Result<Literal, String> {
+    fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("invalid value `{value}`"))
+    }
+
+    Ok(match ty {
+        "int8" => Literal::Byte(parse(value)?),
+        "int16" => Literal::Int16(parse(value)?),
+        "int32" => Literal::Int(parse(value)?),
+        "int64" => Literal::Int64(parse(value)?),
+        "int128" => Literal::Int128(parse(value)?),
+        "uint8" => Literal::UByte(parse(value)?),
+        "uint16" => Literal::UInt16(parse(value)?),
+        "uint32" => Literal::UInt(parse(value)?),
+        "uint64" => Literal::UInt64(parse(value)?),
+        "uint128" => Literal::UInt128(parse(value)?),
+        "int" => Literal::UnsuffixedInt(parse(value)?),
+        "float32" => Literal::Float(parse(value)?),
+        "float64" => Literal::Float64(parse(value)?),
+        "float" => Literal::UnsuffixedFloat(parse(value)?),
+        "bool" => Literal::Boolean(parse(value)?),
+        "str" => Literal::Str(value.into()),
+        "error" => Literal::Unexpected(value.into()),
+
+        "char" => {
+            let mut chars = value.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Literal::Char(c),
+                _ => return Err(format!("expected one char, found `{value}`")),
+            }
+        }
+
+        _ => return Err(format!("unknown type of literal `{ty}`")),
+    })
+}
+
+fn make_behaviour(text: &str) -> Result<Behaviour, String> {
+    match text {
+        "=" => Ok(Behaviour::Copy),
+        "<-" => Ok(Behaviour::Move),
+        "->" => Ok(Behaviour::Ref),
+        _ => Err(format!("expected one of `=`, `<-`, `->`, found `{text}`")),
+    }
+}
+
+/// The text of a value of a literal which is not `bytes`.
+fn literal_text(lit: &Literal) -> String {
+    match lit {
+        Literal::Byte(x) => x.to_string(),
+        Literal::Int16(x) => x.to_string(),
+        Literal::Int(x) => x.to_string(),
+        Literal::Int64(x) => x.to_string(),
+        Literal::Int128(x) => x.to_string(),
+        Literal::UByte(x) => x.to_string(),
+        Literal::UInt16(x) => x.to_string(),
+        Literal::UInt(x) => x.to_string(),
+        Literal::UInt64(x) => x.to_string(),
+        Literal::UInt128(x) => x.to_string(),
+        Literal::UnsuffixedInt(x) => x.to_string(),
+        // `Debug` writes enough digits to read the same number back.
+        Literal::Float(x) => format!("{x:?}"),
+        Literal::Float64(x) => format!("{x:?}"),
+        Literal::UnsuffixedFloat(x) => format!("{x:?}"),
+        Literal::Char(x) => x.to_string(),
+        Literal::Boolean(x) => x.to_string(),
+        Literal::Str(x) | Literal::Unexpected(x) => x.clone(),
+        Literal::ByteStr(_) => unreachable!(),
+    }
+}
+
+fn sexp(reader: &mut Reader) -> Result<Sexp, String> {
+    reader.skip_space();
+
+    if reader.eat("(") {
+        let mut xs = Vec::new();
+
+        while !reader.eat(")") {
+            if reader.rest().is_empty() {
+                return Err(reader.error("`)`"));
+            }
+
+            xs.push(sexp(reader)?);
+        }
+
+        return Ok(Sexp::List(xs));
+    }
+
+    if reader.rest().starts_with('"') {
+        return Ok(Sexp::Str(reader.string()?));
+    }
+
+    match reader.take_while(|c| !c.is_whitespace() && !"()\"".contains(c)) {
+        "" => Err(reader.error("an S-expression")),
+        x => Ok(Sexp::atom(x)),
+    }
+}
+
+fn doc_to_sexp(doc: &Option<String>) -> Option<Sexp> {
+    doc.clone().map(|x| Sexp::list("doc", [Sexp::Str(x)]))
+}
+
+/// The optional `(doc "..")` at the end of a declaration.
+fn doc_from_sexp(rest: &[Sexp]) -> Result<Option<String>, String> {
+    match rest {
+        [] => Ok(None),
+        [x] => match x.args("doc")? {
+            [Sexp::Str(x)] => Ok(Some(x.clone())),
+            _ => Err(format!("expected `(doc \"..\")`, found `{x}`")),
+        },
+        _ => Err(format!("unexpected `{}`", rest[1])),
+    }
+}
+
+impl From<&Literal> for Sexp {
+    fn from(lit: &Literal) -> Sexp {
+        let value = match lit {
+            // `1` and `1.5`, but not `inf`, which is a name.
+            Literal::UnsuffixedInt(_) => return Sexp::Atom(literal_text(lit)),
+            Literal::UnsuffixedFloat(x) if x.is_finite() => return Sexp::Atom(literal_text(lit)),
+            Literal::Str(x) => return Sexp::Str(x.clone()),
+
+            Literal::ByteStr(x) => {
+                return Sexp::list("bytes", x.iter().map(|b| Sexp::Atom(b.to_string())))
+            }
+
+            Literal::Char(_) | Literal::Unexpected(_) => Sexp::Str(literal_text(lit)),
+            _ => Sexp::Atom(literal_text(lit)),
+        };
+
+        Sexp::list(lit.type_name(), [value])
+    }
+}
+
+impl TryFrom<&Sexp> for Literal {
+    type Error = String;
+
+    fn try_from(sexp: &Sexp) -> Result<Self, String> {
+        match sexp {
+            Sexp::Atom(x) if x.contains(['.', 'e', 'E']) => make_literal("float", x),
+            Sexp::Atom(x) => make_literal("int", x),
+            Sexp::Str(x) => Ok(Literal::Str(x.clone())),
+
+            Sexp::List(xs) => match &xs[..] {
+                [Sexp::Atom(ty), bytes @ ..] if ty == "bytes" => Ok(Literal::ByteStr(
+                    bytes
+                        .iter()
+                        .map(|x| {
+                            let x = x.as_atom()?;
+                            x.parse().map_err(|_| format!("invalid byte `{x}`"))
+                        })
+                        .collect::<Result<_, String>>()?,
+                )),
+
+                [Sexp::Atom(ty), Sexp::Atom(x) | Sexp::Str(x)] => make_literal(ty, x),
+
+                _ => Err(format!("expected a literal, found `{sexp}`")),
+            },
+        }
+    }
+}
+
+impl From<&BinaryOp> for Sexp {
+    fn from(op: &BinaryOp) -> Sexp {
+        Sexp::Atom(op.to_string())
+    }
+}
+
+impl TryFrom<&Sexp> for BinaryOp {
+    type Error = String;
+
+    fn try_from(sexp: &Sexp) -> Result<Self, String> {
+        BinaryOp::try_from(sexp.as_atom()?)
+    }
+}
+
+fn is_behaviour(sexp: &Sexp) -> bool {
+    matches!(sexp, Sexp::Atom(x) if make_behaviour(x).is_ok())
+}
+
+/// Write the nodes of a tree whose expressions are in `exprs`.
+///
+/// The ids are not written, the decoded nodes are numbered again.
+struct Writer<'a> {
+    exprs: &'a Exprs,
+}
+
+impl Writer<'_> {
+    fn expr(&self, id: ExprId) -> Sexp {
+        match &self.exprs[id].kind {
+            Expression::Literal(x) => x.into(),
+
+            Expression::Binary { lhs, op, rhs } => {
+                Sexp::List(vec![op.into(), self.expr(*lhs), self.expr(*rhs)])
+            }
+
+            Expression::Ident(x) => Sexp::atom(x.as_str()),
+
+            Expression::FuncCall { ident, args } => Sexp::list(
+                "call",
+                std::iter::once(Sexp::atom(ident.as_str()))
+                    .chain(args.iter().map(|x| self.expr(*x))),
+            ),
+
+            // the strings are the text, so a string literal is written with its type.
+            Expression::Interpolated { parts } => Sexp::list(
+                "interp",
+                parts.iter().map(|x| match x {
+                    InterpolatedPart::Str(x) => Sexp::Str(x.clone()),
+                    InterpolatedPart::Expr(x) => match &self.exprs[*x].kind {
+                        Expression::Literal(Literal::Str(x)) => {
+                            Sexp::list("str", [Sexp::Str(x.clone())])
+                        }
+                        _ => self.expr(*x),
+                    },
+                }),
+            ),
+
+            Expression::None => Sexp::List(vec![]),
+
+            Expression::Error => Sexp::list("error", []),
+        }
+    }
+
+    fn block(&self, block: &Block) -> Sexp {
+        Sexp::list("block", block.block.iter().map(|x| self.stmt(x)))
+    }
+
+    fn stmt(&self, stmt: &Stmt) -> Sexp {
+        match &stmt.kind {
+            Statement::Block(x) => self.block(x),
+
+            // (fn add ((a <- int32) (b <- int32)) (block ..) (doc ".."))
+            Statement::FuncDecl {
+                doc,
+                ident,
+                params,
+                body,
+            } => {
+                let params = params.iter().map(|(name, bhv, ty)| {
+                    Sexp::List(vec![
+                        Sexp::atom(name.as_str()),
+                        Sexp::Atom(bhv.to_string()),
+                        Sexp::atom(ty.as_str()),
+                    ])
+                });
+
+                Sexp::list(
+                    "fn",
+                    [
+                        Sexp::atom(ident.as_str()),
+                        Sexp::List(params.collect()),
+                        self.block(body),
+                    ]
+                    .into_iter()
+                    .chain(doc_to_sexp(doc)),
+                )
+            }
+
+            // (let mut x int32 = 1 (doc ".."))
+            Statement::VariableDecl {
+                doc,
+                ident,
+                ty,
+                mutable,
+                behaviour,
+                value,
+            } => Sexp::list(
+                "let",
+                mutable
+                    .then(|| Sexp::atom("mut"))
+                    .into_iter()
+                    .chain([Sexp::atom(ident.as_str())])
+                    .chain((!ty.is_empty()).then(|| Sexp::atom(ty.as_str())))
+                    .chain([Sexp::Atom(behaviour.to_string()), self.expr(*value)])
+                    .chain(doc_to_sexp(doc)),
+            ),
+
+            Statement::Return(x) => match self.exprs[*x].kind {
+                Expression::None => Sexp::list("return", []),
+                _ => Sexp::list("return", [self.expr(*x)]),
+            },
+
+            Statement::If { cond, block, els } => Sexp::list(
+                "if",
+                [self.expr(*cond), self.block(block)]
+                    .into_iter()
+                    .chain(els.as_ref().map(|x| self.block(x))),
+            ),
+
+            Statement::Error => Sexp::list("error", []),
+        }
+    }
+}
+
+/// Read the nodes of a tree, the expressions are put in `exprs` with their
+/// children before them, like the parser puts them.
+#[derive(Default)]
+struct Builder {
+    exprs: Exprs,
+}
+
+impl Builder {
+    fn expr(&mut self, sexp: &Sexp) -> Result<ExprId, String> {
+        let kind = self.expression(sexp)?;
+        Ok(self.exprs.alloc(kind.into()))
+    }
+
+    fn expression(&mut self, sexp: &Sexp) -> Result<Expression, String> {
+        let xs = match sexp {
+            // the names never start with a digit or a "-".
+            Sexp::Atom(x) if x.starts_with(|c: char| c.is_ascii_digit() || c == '-') => {
+                return Ok(Expression::Literal(sexp.try_into()?))
+            }
+            Sexp::Atom(x) => return Ok(Expression::Ident(x.as_str().into())),
+            Sexp::Str(_) => return Ok(Expression::Literal(sexp.try_into()?)),
+            Sexp::List(xs) => xs,
+        };
+
+        let Some((head, rest)) = xs.split_first() else {
+            return Ok(Expression::None);
+        };
+
+        let head = head.as_atom()?;
+
+        if let Ok(op) = BinaryOp::try_from(head) {
+            return match rest {
+                [lhs, rhs] => Ok(Expression::Binary {
+                    lhs: self.expr(lhs)?,
+                    op,
+                    rhs: self.expr(rhs)?,
+                }),
+                _ => Err(format!("expected `({head} lhs rhs)`, found `{sexp}`")),
+            };
+        }
+
+        Ok(match (head, rest) {
+            ("call", [ident, args @ ..]) => Expression::FuncCall {
+                ident: ident.as_atom()?.into(),
+                args: args
+                    .iter()
+                    .map(|x| self.expr(x))
+                    .collect::<Result<_, _>>()?,
+            },
+
+            ("interp", parts) => Expression::Interpolated {
+                parts: parts
+                    .iter()
+                    .map(|x| match x {
+                        Sexp::Str(x) => Ok(InterpolatedPart::Str(x.clone())),
+                        x => Ok(InterpolatedPart::Expr(self.expr(x)?)),
+                    })
+                    .collect::<Result<_, String>>()?,
+            },
+
+            ("error", []) => Expression::Error,
+
+            _ => Expression::Literal(sexp.try_into()?),
+        })
+    }
+
+    fn block(&mut self, sexp: &Sexp) -> Result<Block, String> {
+        Ok(Block {
+            block: sexp
+                .args("block")?
+                .iter()
+                .map(|x| self.stmt(x))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn stmt(&mut self, sexp: &Sexp) -> Result<Stmt, String> {
+        let (head, rest) = match sexp.as_list()? {
+            [head, rest @ ..] => (head.as_atom()?, rest),
+            [] => return Err("expected a statement, found `()`".into()),
+        };
+
+        let kind = match (head, rest) {
+            ("block", _) => Statement::Block(self.block(sexp)?),
+
+            ("fn", [ident, params, body, doc @ ..]) => Statement::FuncDecl {
+                doc: doc_from_sexp(doc)?,
+                ident: ident.as_atom()?.into(),
+                params: params
+                    .as_list()?
+                    .iter()
+                    .map(|x| match x.as_list()? {
+                        [name, bhv, ty] => Ok((
+                            name.as_atom()?.into(),
+                            make_behaviour(bhv.as_atom()?)?,
+                            ty.as_atom()?.into(),
+                        )),
+                        _ => Err(format!("expected `(name behaviour type)`, found `{x}`")),
+                    })
+                    .collect::<Result<_, String>>()?,
+                body: self.block(body)?,
+            },
+
+            ("let", mut rest) => {
+                // "mut" is the name in `(let mut = 1)`, like in the source.
+                let mutable =
+                    matches!(rest, [Sexp::Atom(x), y, ..] if x == "mut" && !is_behaviour(y));
+                if mutable {
+                    rest = &rest[1..];
+                }
+
+                let (ident, ty, rest) = match rest {
+                    [ident, bhv, ..] if is_behaviour(bhv) => (ident, "", &rest[1..]),
+                    [ident, ty, rest @ ..] => (ident, ty.as_atom()?, rest),
+                    _ => return Err(format!("expected a variable, found `{sexp}`")),
+                };
+
+                let [bhv, value, doc @ ..] = rest else {
+                    return Err(format!("expected a value, found `{sexp}`"));
+                };
+
+                Statement::VariableDecl {
+                    doc: doc_from_sexp(doc)?,
+                    ident: ident.as_atom()?.into(),
+                    ty: ty.into(),
+                    mutable,
+                    behaviour: make_behaviour(bhv.as_atom()?)?,
+                    value: self.expr(value)?,
+                }
+            }
+
+            ("return", []) => Statement::Return(self.exprs.alloc(Expression::None.into())),
+
+            ("return", [x]) => Statement::Return(self.expr(x)?),
+
+            ("if", [cond, block, els @ ..]) => Statement::If {
+                cond: self.expr(cond)?,
+                block: self.block(block)?,
+                els: match els {
+                    [] => None,
+                    [x] => Some(self.block(x)?),
+                    _ => return Err(format!("unexpected `{}`", els[1])),
+                },
+            },
+
+            ("error", []) => Statement::Error,
+
+            _ => return Err(format!("expected a statement, found `{sexp}`")),
+        };
+
+        Ok(kind.into())
+    }
+}
+
+impl From<&Module> for Sexp {
+    fn from(module: &Module) -> Sexp {
+        let writer = Writer {
+            exprs: &module.exprs,
+        };
+
+        Sexp::list("module", module.items.iter().map(|x| writer.stmt(x)))
+    }
+}
+
+impl TryFrom<&Sexp> for Module {
+    type Error = String;
+
+    fn try_from(sexp: &Sexp) -> Result<Self, String> {
+        let mut builder = Builder::default();
+
+        let mut module = Module {
+            items: sexp
+                .args("module")?
+                .iter()
+                .map(|x| builder.stmt(x))
+                .collect::<Result<_, _>>()?,
+            exprs: builder.exprs,
+            span: Span::default(),
+        };
+
+        NodeIds::default().visit_module(&mut module);
+        Ok(module)
+    }
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
@@ -39,6 +40,93 @@ pub enum BinaryOp {
     Is,         // :
 }
 
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Lsh => "<<",
+            BinaryOp::Rsh => ">>",
+            BinaryOp::And => "&",
+            BinaryOp::Or => "|",
+            BinaryOp::Xor => "^",
+            BinaryOp::Not => "~",
+            BinaryOp::LogicalNot => "!",
+            BinaryOp::LogicalAnd => "&&",
+            BinaryOp::LogicalOr => "||",
+            BinaryOp::Lt => "<",
+            BinaryOp::Lte => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Gte => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Assign => "=",
+            BinaryOp::AddEq => "+=",
+            BinaryOp::SubEq => "-=",
+            BinaryOp::MulEq => "*=",
+            BinaryOp::DivEq => "/=",
+            BinaryOp::ModEq => "%=",
+            BinaryOp::AndEq => "&=",
+            BinaryOp::OrEq => "|=",
+            BinaryOp::XorEq => "^=",
+            BinaryOp::LshEq => "<<=",
+            BinaryOp::RshEq => ">>=",
+            BinaryOp::Ref => "->",
+            BinaryOp::Move => "<-",
+            BinaryOp::Scope => "::",
+            BinaryOp::Is => ":",
+        })
+    }
+}
+
+impl TryFrom<&str> for BinaryOp {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "+" => Ok(Self::Add),
+            "-" => Ok(Self::Sub),
+            "*" => Ok(Self::Mul),
+            "/" => Ok(Self::Div),
+            "%" => Ok(Self::Mod),
+            "<<" => Ok(Self::Lsh),
+            ">>" => Ok(Self::Rsh),
+            "&" => Ok(Self::And),
+            "|" => Ok(Self::Or),
+            "^" => Ok(Self::Xor),
+            "~" => Ok(Self::Not),
+            "!" => Ok(Self::LogicalNot),
+            "&&" => Ok(Self::LogicalAnd),
+            "||" => Ok(Self::LogicalOr),
+            "<" => Ok(Self::Lt),
+            "<=" => Ok(Self::Lte),
+            ">" => Ok(Self::Gt),
+            ">=" => Ok(Self::Gte),
+            "==" => Ok(Self::Eq),
+            "!=" => Ok(Self::Ne),
+            "=" => Ok(Self::Assign),
+            "+=" => Ok(Self::AddEq),
+            "-=" => Ok(Self::SubEq),
+            "*=" => Ok(Self::MulEq),
+            "/=" => Ok(Self::DivEq),
+            "%=" => Ok(Self::ModEq),
+            "&=" => Ok(Self::AndEq),
+            "|=" => Ok(Self::OrEq),
+            "^=" => Ok(Self::XorEq),
+            "<<=" => Ok(Self::LshEq),
+            ">>=" => Ok(Self::RshEq),
+            "->" => Ok(Self::Ref),
+            "<-" => Ok(Self::Move),
+            "::" => Ok(Self::Scope),
+            ":" => Ok(Self::Is),
+            _ => Err(format!("unknown operator `{value}`")),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum KeyWord {
     LET,
@@ -73,26 +161,81 @@ impl TryFrom<String> for KeyWord {
     }
 }
 
-#[derive(Debug, PartialOrd, PartialEq)]
+/// In JSON, `{"type":"int64","value":1}`, the type is the one of `type_name`.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 pub enum Literal {
+    #[serde(rename = "int8")]
     Byte(i8),
+    #[serde(rename = "int16")]
     Int16(i16),
+    #[serde(rename = "int32")]
     Int(i32), // int32, default integral type
+    #[serde(rename = "int64")]
     Int64(i64),
+    #[serde(rename = "int128", with = "super::json::text")]
     Int128(i128),
 
+    #[serde(rename = "uint8")]
     UByte(u8),
+    #[serde(rename = "uint16")]
     UInt16(u16),
+    #[serde(rename = "uint32")]
     UInt(u32), // uint32, default unsigned integral type
+    #[serde(rename = "uint64")]
     UInt64(u64),
+    #[serde(rename = "uint128", with = "super::json::text")]
     UInt128(u128),
+    /// An integer without suffix, its type is inferred where it is, like
+    /// `int64` in `let x: int64 = 1;`.
+    #[serde(rename = "int", with = "super::json::text")]
+    UnsuffixedInt(u128),
 
+    #[serde(rename = "float32")]
     Float(f32), // f32, default floating numeric type
+    #[serde(rename = "float64")]
     Float64(f64),
+    /// A float without suffix, inferred like `UnsuffixedInt`.
+    #[serde(rename = "float")]
+    UnsuffixedFloat(f64),
 
-    Char(String),
+    #[serde(rename = "char")]
+    Char(char),
+    #[serde(rename = "bool")]
     Boolean(bool),
+    #[serde(rename = "str")]
     Str(String), // string OR raw string
+    #[serde(rename = "bytes")]
+    ByteStr(Vec<u8>),
 
+    #[serde(rename = "error")]
     Unexpected(String),
 }
+
+impl Literal {
+    /// The name of the type of the literal, like "int32", or "int" and
+    /// "float" without suffix.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Literal::Byte(_) => "int8",
+            Literal::Int16(_) => "int16",
+            Literal::Int(_) => "int32",
+            Literal::Int64(_) => "int64",
+            Literal::Int128(_) => "int128",
+            Literal::UByte(_) => "uint8",
+            Literal::UInt16(_) => "uint16",
+            Literal::UInt(_) => "uint32",
+            Literal::UInt64(_) => "uint64",
+            Literal::UInt128(_) => "uint128",
+            Literal::UnsuffixedInt(_) => "int",
+            Literal::Float(_) => "float32",
+            Literal::Float64(_) => "float64",
+            Literal::UnsuffixedFloat(_) => "float",
+            Literal::Char(_) => "char",
+            Literal::Boolean(_) => "bool",
+            Literal::Str(_) => "str",
+            Literal::ByteStr(_) => "bytes",
+            Literal::Unexpected(_) => "error",
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::compiler::firelang_lexer::symbol::Symbol;
+
+use super::node::*;
+use super::token::Literal;
+
+/// Visit the tree by shared reference.
+///
+/// A pass overrides the `visit_*` methods of the nodes it cares about, and
+/// calls the `walk_*` function of the node from them to go on to its children.
+/// The default methods only walk, so every node is visited.
+///
+/// The expressions are in the `Exprs` of the module, which is given along
+/// with the nodes to look their children up.
+pub trait Visitor: Sized {
+    fn visit_module(&mut self, module: &Module) {
+        walk_module(self, module);
+    }
+
+    fn visit_stmt(&mut self, exprs: &Exprs, stmt: &Stmt) {
+        walk_stmt(self, exprs, stmt);
+    }
+
+    fn visit_block(&mut self, exprs: &Exprs, block: &Block) {
+        walk_block(self, exprs, block);
+    }
+
+    /// A parameter of a function, `(name, behaviour, type)`.
+    fn visit_param(&mut self, _param: &(Symbol, Behaviour, Symbol)) {}
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: &Expr) {
+        walk_expr(self, exprs, expr);
+    }
+
+    fn visit_interpolated_part(&mut self, exprs: &Exprs, part: &InterpolatedPart) {
+        walk_interpolated_part(self, exprs, part);
+    }
+
+    fn visit_literal(&mut self, _lit: &Literal) {}
+}
+
+pub fn walk_module<V: Visitor>(v: &mut V, module: &Module) {
+    for x in &module.items {
+        v.visit_stmt(&module.exprs, x);
+    }
+}
+
+pub fn walk_stmt<V: Visitor>(v: &mut V, exprs: &Exprs, stmt: &Stmt) {
+    match &stmt.kind {
+        Statement::Block(x) => v.visit_block(exprs, x),
+
+        Statement::FuncDecl { params, body, .. } => {
+            for x in params {
+                v.visit_param(x);
+            }
+
+            v.visit_block(exprs, body);
+        }
+
+        Statement::VariableDecl { value, .. } => v.visit_expr(exprs, &exprs[*value]),
+
+        Statement::Return(x) => v.visit_expr(exprs, &exprs[*x]),
+
+        Statement::If { cond, block, els } => {
+            v.visit_expr(exprs, &exprs[*cond]);
+            v.visit_block(exprs, block);
+
+            if let Some(x) = els {
+                v.visit_block(exprs, x);
+            }
+        }
+
+        Statement::Error => (),
+    }
+}
+
+pub fn walk_block<V: Visitor>(v: &mut V, exprs: &Exprs, block: &Block) {
+    for x in &block.block {
+        v.visit_stmt(exprs, x);
+    }
+}
+
+pub fn walk_expr<V: Visitor>(v: &mut V, exprs: &Exprs, expr: &Expr) {
+    match &expr.kind {
+        Expression::Literal(x) => v.visit_literal(x),
+
+        Expression::Binary { lhs, rhs, .. } => {
+            v.visit_expr(exprs, &exprs[*lhs]);
+            v.visit_expr(exprs, &exprs[*rhs]);
+        }
+
+        Expression::FuncCall { args, .. } => {
+            for x in args {
+                v.visit_expr(exprs, &exprs[*x]);
+            }
+        }
+
+        Expression::Interpolated { parts } => {
+            for x in parts {
+                v.visit_interpolated_part(exprs, x);
+            }
+        }
+
+        Expression::Ident(_) | Expression::None | Expression::Error => (),
+    }
+}
+
+pub fn walk_interpolated_part<V: Visitor>(v: &mut V, exprs: &Exprs, part: &InterpolatedPart) {
+    match part {
+        InterpolatedPart::Str(_) => (),
+        InterpolatedPart::Expr(x) => v.visit_expr(exprs, &exprs[*x]),
+    }
+}
+
+/// Visit the tree by mutable reference, to rewrite the nodes in place.
+///
+/// A pass rewrites a node by assigning its `kind`, which keeps its id, e.g.
+/// constant folding overrides `visit_expr`, walks the children first and then
+/// replaces a `Binary` of two literals with a `Literal`.
+///
+/// The expressions are visited by their `ExprId`, since the pass has the
+/// whole arena to change them.
+pub trait MutVisitor: Sized {
+    fn visit_module(&mut self, module: &mut Module) {
+        walk_module_mut(self, module);
+    }
+
+    fn visit_stmt(&mut self, exprs: &mut Exprs, stmt: &mut Stmt) {
+        walk_stmt_mut(self, exprs, stmt);
+    }
+
+    fn visit_block(&mut self, exprs: &mut Exprs, block: &mut Block) {
+        walk_block_mut(self, exprs, block);
+    }
+
+    /// A parameter of a function, `(name, behaviour, type)`.
+    fn visit_param(&mut self, _param: &mut (Symbol, Behaviour, Symbol)) {}
+
+    fn visit_expr(&mut self, exprs: &mut Exprs, id: ExprId) {
+        walk_expr_mut(self, exprs, id);
+    }
+
+    fn visit_interpolated_part(&mut self, exprs: &mut Exprs, part: &mut InterpolatedPart) {
+        walk_interpolated_part_mut(self, exprs, part);
+    }
+
+    fn visit_literal(&mut self, _lit: &mut Literal) {}
+}
+
+pub fn walk_module_mut<V: MutVisitor>(v: &mut V, module: &mut Module) {
+    for x in &mut module.items {
+        v.visit_stmt(&mut module.exprs, x);
+    }
+}
+
+pub fn walk_stmt_mut<V: MutVisitor>(v: &mut V, exprs: &mut Exprs, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        Statement::Block(x) => v.visit_block(exprs, x),
+
+        Statement::FuncDecl { params, body, .. } => {
+            for x in params {
+                v.visit_param(x);
+            }
+
+            v.visit_block(exprs, body);
+        }
+
+        Statement::VariableDecl { value, .. } => v.visit_expr(exprs, *value),
+
+        Statement::Return(x) => v.visit_expr(exprs, *x),
+
+        Statement::If { cond, block, els } => {
+            v.visit_expr(exprs, *cond);
+            v.visit_block(exprs, block);
+
+            if let Some(x) = els {
+                v.visit_block(exprs, x);
+            }
+        }
+
+        Statement::Error => (),
+    }
+}
+
+pub fn walk_block_mut<V: MutVisitor>(v: &mut V, exprs: &mut Exprs, block: &mut Block) {
+    for x in &mut block.block {
+        v.visit_stmt(exprs, x);
+    }
+}
+
+pub fn walk_expr_mut<V: MutVisitor>(v: &mut V, exprs: &mut Exprs, id: ExprId) {
+    match &mut exprs[id].kind {
+        Expression::Literal(x) => v.visit_literal(x),
+
+        Expression::Binary { lhs, rhs, .. } => {
+            let (lhs, rhs) = (*lhs, *rhs);
+            v.visit_expr(exprs, lhs);
+            v.visit_expr(exprs, rhs);
+        }
+
+        Expression::FuncCall { args, .. } => {
+            for x in args.clone() {
+                v.visit_expr(exprs, x);
+            }
+        }
+
+        Expression::Interpolated { parts } => {
+            // the parts are taken out of the arena while the pass has it.
+            let mut parts = std::mem::take(parts);
+
+            for x in &mut parts {
+                v.visit_interpolated_part(exprs, x);
+            }
+
+            if let Expression::Interpolated { parts: x } = &mut exprs[id].kind {
+                *x = parts;
+            }
+        }
+
+        Expression::Ident(_) | Expression::None | Expression::Error => (),
+    }
+}
+
+pub fn walk_interpolated_part_mut<V: MutVisitor>(
+    v: &mut V,
+    exprs: &mut Exprs,
+    part: &mut InterpolatedPart,
+) {
+    match part {
+        InterpolatedPart::Str(_) => (),
+        InterpolatedPart::Expr(x) => v.visit_expr(exprs, *x),
+    }
+}
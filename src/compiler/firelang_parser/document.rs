@@ -0,0 +1,363 @@
+use crate::compiler::firelang_lexer::lexer::{
+    columns, Lexer, LiteralKind, Span, StrPart, Token, TokenKind, TokenSource, MAX_LOOKAHEAD,
+};
+use crate::compiler::firelang_parser::ast::node::{Exprs, NodeId, NodeIds, Stmt};
+use crate::compiler::firelang_parser::ast::node_impl::same_stmt;
+use crate::compiler::firelang_parser::parser::{Confusables, Parser, Scoping};
+use crate::error_generator::generator::{Diagnostic, Diagnostics};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A top-level statement of the document.
+#[derive(Debug)]
+pub struct Item {
+    /// From the end of the previous item to the last token of this one.
+    pub span: Span,
+    /// A `Statement::Error` if it has a syntax error.
+    pub stmt: Stmt,
+    /// The expressions of the statement, each item has its own arena so it
+    /// is reused along with it.
+    pub exprs: Exprs,
+    /// The syntax errors, and the lexical diagnostics of the embedded
+    /// expressions.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The line and column of the first token of each node.
+    pub positions: HashMap<NodeId, (usize, usize)>,
+    /// The declarations and the scopes, the lints are checked with them
+    /// since they depend on the other items.
+    scoping: Vec<Scoping>,
+    /// The index of the first token which the parser has not looked at.
+    scanned: usize,
+}
+
+/// The tokens and items rebuilt by an edit, the others are reused.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Changed {
+    pub tokens: Range<usize>,
+    pub items: Range<usize>,
+}
+
+/// Document Struct
+/// A source file kept lexed and parsed, which is relexed and reparsed
+/// incrementally when being edited.
+#[derive(Debug)]
+pub struct Document {
+    src: String,
+    /// The byte offsets where the lines start.
+    lines: Vec<usize>,
+    tokens: Vec<Token<'static>>,
+    /// The lexical diagnostics, with the offset of the token reporting them.
+    diagnostics: Vec<(usize, Diagnostic)>,
+    items: Vec<Item>,
+    /// The first node id which no item has, the items parsed again get new
+    /// ids, so the reused ones keep theirs.
+    next_id: u32,
+}
+
+/// Feed the parser with the tokens of the document.
+struct Tokens<'t> {
+    tokens: &'t [Token<'static>],
+    pos: usize,
+    diagnostics: Diagnostics,
+}
+
+impl TokenSource<'static> for Tokens<'_> {
+    fn next_token(&mut self) -> Token<'static> {
+        // the last token is always `Eof`.
+        let x = self.tokens[self.pos.min(self.tokens.len() - 1)].clone();
+        self.pos += 1;
+        x
+    }
+
+    fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+}
+
+fn shift(x: usize, delta: isize) -> usize {
+    (x as isize + delta) as usize
+}
+
+impl Item {
+    /// Whether the items have the same statement, wherever their expressions
+    /// are in their arenas.
+    pub fn same_stmt(&self, other: &Item) -> bool {
+        same_stmt(&self.exprs, &self.stmt, &other.exprs, &other.stmt)
+    }
+}
+
+impl Document {
+    /// Construct a new Document
+    pub fn new(src: &str) -> Document {
+        let mut doc = Document {
+            src: String::new(),
+            lines: vec![0],
+            tokens: vec![Lexer::new("").next_token().into_owned()],
+            diagnostics: Vec::new(),
+            items: Vec::new(),
+            next_id: 0,
+        };
+
+        doc.apply_edit(Span { start: 0, end: 0 }, src);
+        doc
+    }
+
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    pub fn tokens(&self) -> &[Token<'static>] {
+        &self.tokens
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// The lexical diagnostics, then the syntax errors.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .map(|(_, x)| x)
+            .chain(self.items.iter().flat_map(|x| &x.diagnostics))
+    }
+
+    /// The warnings about the declarations, like the confusable identifiers.
+    pub fn lints(&self) -> Diagnostics {
+        let mut confusables = Confusables::default();
+
+        for x in self.items.iter().flat_map(|x| &x.scoping) {
+            confusables.apply(x);
+        }
+
+        confusables.lints().clone()
+    }
+
+    /// The line and column of the first token of each node.
+    pub fn positions(&self) -> HashMap<NodeId, (usize, usize)> {
+        self.items
+            .iter()
+            .flat_map(|x| &x.positions)
+            .map(|(&k, &v)| (k, v))
+            .collect()
+    }
+
+    /// The line and column of the lexer at `offset`.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.lines.partition_point(|&x| x <= offset);
+        let column = columns(&self.src[self.lines[line - 1]..offset]);
+
+        (line, column)
+    }
+
+    /// Replace the bytes in `range` with `text`, then relex and reparse the
+    /// affected region only.
+    ///
+    /// Panics if the range is not on char boundaries, like `String::replace_range`.
+    pub fn apply_edit(&mut self, range: Span, text: &str) -> Changed {
+        let (a, b) = (range.start, range.end);
+        let delta = text.len() as isize - (b - a) as isize;
+
+        self.src.replace_range(a..b, text);
+
+        let first = self.lines.partition_point(|&x| x <= a);
+        let last = self.lines.partition_point(|&x| x <= b);
+        let lines: Vec<usize> = text
+            .match_indices('\n')
+            .map(|(k, _)| a + k + 1)
+            .chain(self.lines[last..].iter().map(|&x| shift(x, delta)))
+            .collect();
+        let line_delta = lines.len() as isize - (self.lines.len() - first) as isize;
+
+        self.lines.truncate(first);
+        self.lines.extend(lines);
+
+        // relex from the first token which may have looked at the edited bytes.
+        let i = self
+            .tokens
+            .partition_point(|x| x.span.end + MAX_LOOKAHEAD <= a);
+        let start = self.tokens[i].span.start;
+        let (line, column) = self.position(start);
+
+        // the old tokens can only be reused on a line after the edit,
+        // where both the bytes and the columns are unchanged.
+        let end = a + text.len();
+        let sync = self.src[end..].find('\n').map(|k| end + k + 1);
+        let old_from = self.tokens.partition_point(|x| x.span.start < b);
+
+        let mut lexer = Lexer::new(&self.src[start..]);
+        lexer.line = line;
+        lexer.column = column;
+
+        let mut tokens = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        let reuse = loop {
+            let q = start + lexer.offset();
+
+            if sync.is_some_and(|x| q >= x) {
+                let old = shift(q, -delta);
+
+                if let Ok(j) = self.tokens[old_from..].binary_search_by_key(&old, |x| x.span.start)
+                {
+                    break Some(old_from + j);
+                }
+            }
+
+            let mut tok = lexer.next_token().into_owned();
+            tok.span.start += start;
+            tok.span.end += start;
+
+            for x in std::mem::take(&mut lexer.diagnostics) {
+                diagnostics.push((tok.span.start, x));
+            }
+
+            let eof = tok.kind == TokenKind::Eof;
+            tokens.push(tok);
+
+            if eof {
+                break None;
+            }
+        };
+
+        let j = reuse.unwrap_or(self.tokens.len());
+        let boundary = reuse.map(|j| self.tokens[j].span.start);
+        let changed_tokens = i..i + tokens.len();
+        let index_delta = changed_tokens.end as isize - j as isize;
+
+        let tail = self.tokens.split_off(j);
+        self.tokens.truncate(i);
+        self.tokens.extend(tokens);
+        self.tokens.extend(tail.into_iter().map(|mut x| {
+            x.span.start = shift(x.span.start, delta);
+            x.span.end = shift(x.span.end, delta);
+            x.line = shift(x.line, line_delta);
+
+            // the embedded expressions know where they are too.
+            if let TokenKind::Literal {
+                kind: LiteralKind::InterpolatedStr { parts, .. },
+                ..
+            } = &mut x.kind
+            {
+                for part in parts {
+                    if let StrPart::Expr { line, .. } = part {
+                        *line = shift(*line, line_delta);
+                    }
+                }
+            }
+
+            x
+        }));
+
+        let (kept, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut self.diagnostics)
+            .into_iter()
+            .partition(|(k, _)| *k < start);
+        self.diagnostics = kept;
+        self.diagnostics.extend(diagnostics);
+
+        if let Some(boundary) = boundary {
+            self.diagnostics
+                .extend(
+                    rest.into_iter()
+                        .filter(|(k, _)| *k >= boundary)
+                        .map(|(k, mut x)| {
+                            x.line = shift(x.line, line_delta);
+                            (shift(k, delta), x)
+                        }),
+                );
+        }
+
+        // reparse from the first item which may have looked at the relexed tokens.
+        let k = self.items.partition_point(|x| x.scanned <= i);
+        let mut old_items = self.items.split_off(k);
+        let mut offset = self.items.last().map_or(0, |x| x.span.end);
+        let mut reused = self.items.len()..self.items.len();
+        let sync = reuse.map(|_| self.tokens[changed_tokens.end].span.start);
+
+        let from = self.tokens.partition_point(|x| x.span.start < offset);
+        let mut parser = Parser::new(Tokens {
+            tokens: &self.tokens,
+            pos: from,
+            diagnostics: Diagnostics::new(),
+        })
+        .with_node_ids(NodeIds::starting_at(self.next_id));
+
+        loop {
+            // the old items after the relexed tokens line up again.
+            if sync.is_some_and(|x| offset >= x) {
+                let old = shift(offset, -delta);
+
+                if let Ok(m) = old_items.binary_search_by_key(&old, |x| x.span.start) {
+                    let tail = old_items.split_off(m);
+                    reused = self.items.len()..self.items.len() + tail.len();
+
+                    self.items.extend(tail.into_iter().map(|mut x| {
+                        x.span.start = shift(x.span.start, delta);
+                        x.span.end = shift(x.span.end, delta);
+                        x.scanned = shift(x.scanned, index_delta);
+
+                        // they are on the lines after the edit, only the lines move.
+                        for x in &mut x.diagnostics {
+                            x.line = shift(x.line, line_delta);
+                        }
+                        for (line, _) in x.positions.values_mut() {
+                            *line = shift(*line, line_delta);
+                        }
+                        for x in &mut x.scoping {
+                            if let Scoping::Declare { line, .. } = x {
+                                *line = shift(*line, line_delta);
+                            }
+                        }
+
+                        x
+                    }));
+
+                    break;
+                }
+            }
+
+            if !parser.has_content() {
+                break;
+            }
+
+            let stmt = parser.parse_stmt_or_error();
+            let collected = parser.take_collected();
+            self.next_id = parser.node_ids().count() as u32;
+
+            let scanned = self
+                .items
+                .last()
+                .map_or(0, |x| x.scanned)
+                .max(parser.source().pos);
+            let end = parser.offset().max(offset);
+
+            self.items.push(Item {
+                span: Span { start: offset, end },
+                stmt,
+                exprs: collected.exprs,
+                diagnostics: collected
+                    .lexical
+                    .into_iter()
+                    .chain(collected.errors)
+                    .collect(),
+                positions: collected.positions,
+                scoping: collected.scoping,
+                scanned,
+            });
+
+            offset = end;
+        }
+
+        // the reused items are at the end.
+        let changed_items = k..reused.start;
+
+        Changed {
+            tokens: changed_tokens,
+            items: changed_items,
+        }
+    }
+}
@@ -1,17 +1,147 @@
-use crate::compiler::firelang_lexer::lexer::{Lexer, Token, TokenKind};
+use crate::compiler::firelang_lexer::lexer::{
+    Lexer, LiteralKind, Span, StrPart, Token, TokenKind, TokenSource,
+};
+use crate::compiler::firelang_lexer::symbol::Symbol;
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use unicode_security::skeleton;
 
 use crate::compiler::firelang_parser::ast::node::*;
 use crate::compiler::firelang_parser::ast::node_impl::{make_ident, make_lit};
-use crate::compiler::firelang_parser::ast::token::{BinaryOp, KeyWord};
+use crate::compiler::firelang_parser::ast::token::{BinaryOp, KeyWord, Literal};
+use crate::error_generator::generator::Diagnostics;
 
 #[derive(Clone)]
-pub struct Parser<'a> {
-    lex: Lexer<'a>,
+pub struct Parser<'a, S = Lexer<'a>> {
+    lex: S,
+    /// The tokens which have been peeked but not consumed yet.
+    peeked: VecDeque<Token<'a>>,
+    /// The byte offset after the last consumed token.
+    offset: usize,
+    /// The doc comments, by the offset of the token they document.
+    docs: HashMap<usize, String>,
+    /// The names declared in the enclosing scopes, and the warnings about them.
+    confusables: Confusables,
+    /// The declarations and the scopes met so far, for the document which
+    /// checks the confusable names again when an edit moves them.
+    scoping: Vec<Scoping>,
+    /// The syntax errors, the parser goes on after them.
+    errors: Diagnostics,
+    /// What the tokens checked since the last consumed one could have been,
+    /// they are listed when none of them is found.
+    expected: Vec<String>,
+    layout: Layout,
+    ids: NodeIds,
+    /// The expressions parsed so far, the statements refer to them by
+    /// `ExprId`.
+    exprs: Exprs,
+    /// The line and column of the first token of each node, for the
+    /// diagnostics of the later passes.
+    positions: HashMap<NodeId, (usize, usize)>,
 }
 
-static PRECEDENCE: Lazy<HashMap<BinaryOp, i32>> = Lazy::new(|| {
+/// Where the parts of the source which the tree doesn't keep are, for the
+/// tools which print the tree back, like the formatter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout {
+    /// The comments, the doc comments and the shebang.
+    pub comments: Vec<Span>,
+    /// The statements, in the order they start.
+    pub stmts: Vec<Span>,
+    /// The literal tokens, in the order they appear.
+    pub literals: Vec<Span>,
+}
+
+/// A declaration, or the start or the end of a scope, in the order the
+/// parser meets them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scoping {
+    Enter,
+    Leave,
+    Declare {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+/// Warn about the names which look the same as another visible name, like
+/// the latin "a" and the cyrillic "а".
+#[derive(Debug, Clone)]
+pub struct Confusables {
+    /// The names declared in the enclosing scopes, by their confusable
+    /// skeleton, with the line and column of the declaration.
+    scopes: Vec<HashMap<String, (String, usize, usize)>>,
+    lints: Diagnostics,
+}
+
+impl Default for Confusables {
+    fn default() -> Self {
+        Confusables {
+            scopes: vec![HashMap::new()],
+            lints: Diagnostics::new(),
+        }
+    }
+}
+
+impl Confusables {
+    pub fn apply(&mut self, x: &Scoping) {
+        match x {
+            Scoping::Enter => self.scopes.push(HashMap::new()),
+            Scoping::Leave => {
+                self.scopes.pop();
+            }
+            Scoping::Declare { name, line, column } => self.declare(name, *line, *column),
+        }
+    }
+
+    /// Declare `name` in the current scope, and warn if it looks the same as
+    /// another name which is visible here.
+    fn declare(&mut self, name: &str, line: usize, column: usize) {
+        let key: String = skeleton(name).collect();
+
+        for scope in self.scopes.iter().rev() {
+            if let Some((other, ln, col)) = scope.get(&key) {
+                // two ASCII names are told apart easily, like "rn" and "m".
+                if other != name && !(other.is_ascii() && name.is_ascii()) {
+                    self.lints
+                        .warning(
+                            line,
+                            column,
+                            format!("identifier `{name}` is confusable with `{other}`"),
+                        )
+                        .note(format!("`{other}` is declared at line {ln}, col {col}"));
+                }
+
+                break;
+            }
+        }
+
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(key, (name.into(), line, column));
+    }
+
+    /// The warnings about the declarations applied so far.
+    pub fn lints(&self) -> &Diagnostics {
+        &self.lints
+    }
+}
+
+/// What the parser collects besides the tree, the document keeps it by
+/// statement.
+pub(crate) struct Collected {
+    /// The lexical diagnostics of the embedded expressions.
+    pub lexical: Diagnostics,
+    pub errors: Diagnostics,
+    pub exprs: Exprs,
+    pub positions: HashMap<NodeId, (usize, usize)>,
+    pub scoping: Vec<Scoping>,
+}
+
+/// How tightly the binary operators bind, they are all left-associative.
+pub(crate) static PRECEDENCE: Lazy<HashMap<BinaryOp, i32>> = Lazy::new(|| {
     vec![
         (BinaryOp::OrEq, 0),
         (BinaryOp::AndEq, 0),
@@ -50,329 +180,580 @@ static PRECEDENCE: Lazy<HashMap<BinaryOp, i32>> = Lazy::new(|| {
     .collect()
 });
 
-impl Parser<'_> {
-    pub fn new(lex: Lexer) -> Parser {
-        Parser { lex }
+impl<'a, S: TokenSource<'a>> Parser<'a, S> {
+    pub fn new(lex: S) -> Parser<'a, S> {
+        Parser {
+            lex,
+            peeked: VecDeque::new(),
+            offset: 0,
+            docs: HashMap::new(),
+            confusables: Confusables::default(),
+            scoping: Vec::new(),
+            errors: Diagnostics::new(),
+            expected: Vec::new(),
+            layout: Layout::default(),
+            ids: NodeIds::default(),
+            exprs: Exprs::default(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Get the `n`th token after the current one without consuming it.
+    fn peek(&mut self, n: usize) -> &Token<'a> {
+        while self.peeked.len() <= n {
+            let x = self.lex_token();
+            self.peeked.push_back(x);
+        }
+
+        &self.peeked[n]
     }
 
-    fn lookahead(&self) -> Token {
-        self.clone().next().unwrap()
+    fn lookahead(&mut self) -> &Token<'a> {
+        self.peek(0)
     }
 
-    fn next(&mut self) -> Option<Token> {
-        let x = self.lex.next_token();
+    fn lex_token(&mut self) -> Token<'a> {
+        let mut doc: Option<String> = None;
+
+        loop {
+            let x = self.lex.next_token();
 
-        if x.kind == TokenKind::Space {
-            return self.next();
+            // the lexer has reported the illegal characters, skip them with the trivia.
+            match x.kind {
+                TokenKind::Space | TokenKind::Illegal => continue,
+
+                TokenKind::LineComment | TokenKind::Shebang | TokenKind::BlockComment { .. } => {
+                    self.layout.comments.push(x.span);
+                }
+
+                TokenKind::DocComment => {
+                    self.layout.comments.push(x.span);
+
+                    let doc = doc.get_or_insert_with(String::new);
+
+                    if !doc.is_empty() {
+                        doc.push('\n');
+                    }
+
+                    doc.push_str(&x.content);
+                }
+
+                _ => {
+                    if let Some(doc) = doc {
+                        self.docs.insert(x.span.start, doc);
+                    }
+
+                    return x;
+                }
+            }
+        }
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.peek(0);
+        let x = self.peeked.pop_front();
+
+        if let Some(x) = &x {
+            self.offset = x.span.end;
+            self.expected.clear();
         }
 
-        Some(x)
+        x
     }
 
     fn eat(&mut self) {
         self.next().unwrap();
     }
 
-    fn match_tok(&mut self, s: &TokenKind) -> Result<(), String> {
-        let k = self.lookahead().kind;
+    /// Expect `what` at the current position, it is listed if the parsing fails here.
+    fn expect(&mut self, what: impl Into<String>) {
+        let what = what.into();
 
-        if k != *s {
-            return Err(format!(
-                "At line {:?}, col {:?}: Expected {:?}, found {:?}",
-                self.lex.line, self.lex.column, s, k
-            ));
+        if !self.expected.contains(&what) {
+            self.expected.push(what);
         }
+    }
+
+    /// Whether the next token is of `kind`.
+    fn check(&mut self, kind: &TokenKind) -> bool {
+        self.expect(describe(kind));
+        self.lookahead().kind == *kind
+    }
 
-        Ok(())
+    /// Whether the next token is the keyword `kw`.
+    fn check_keyword(&mut self, kw: &KeyWord) -> bool {
+        self.expect(format!("`{}`", kw.to_string().to_lowercase()));
+        self.keyword().as_ref() == Some(kw)
     }
 
-    fn match_keyword(&self, s: &KeyWord) -> Result<(), String> {
-        let k = self.lookahead();
+    /// The keyword at the current position.
+    fn keyword(&mut self) -> Option<KeyWord> {
+        let x = self.lookahead();
 
-        if let Ok(x) = KeyWord::try_from(k.content.clone()) {
-            if x != *s {
-                return Err(format!(
-                    "At line {:?}, col {:?}: Expected keyword <{}>, found keyword <{}>",
-                    self.lex.line, self.lex.column, s, x
-                ));
-            }
-        } else {
-            return Err(format!(
-                "At line {:?}, col {:?}: Expected keyword <{:?}>, found {:?}",
-                self.lex.line, self.lex.column, s, k
-            ));
+        if x.kind != TokenKind::Ident {
+            return None;
         }
 
-        Ok(())
+        KeyWord::try_from(x.content.to_string()).ok()
     }
 
-    fn next_tok_is_op(&mut self) -> Option<BinaryOp> {
-        match self.lookahead().kind {
-            TokenKind::Plus => {
-                self.eat();
+    /// "expected one of `;`, `=`, found `}`", with the tokens expected since the last consumed one.
+    fn unexpected(&mut self) -> String {
+        let found = describe_found(self.lookahead());
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::AddEq);
-                }
+        match &self.expected[..] {
+            [] => format!("unexpected {found}"),
+            [x] => format!("expected {x}, found {found}"),
+            xs => format!("expected one of {}, found {found}", xs.join(", ")),
+        }
+    }
 
-                Some(BinaryOp::Add)
-            }
+    /// Get the operator at the current position and the number of tokens it takes.
+    fn peek_op(&mut self) -> Option<(BinaryOp, usize)> {
+        use TokenKind::*;
 
-            TokenKind::Minus => {
-                self.eat();
+        self.peek(2);
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::SubEq);
-                }
+        let op = match (
+            &self.peeked[0].kind,
+            &self.peeked[1].kind,
+            &self.peeked[2].kind,
+        ) {
+            (Plus, Equal, _) => (BinaryOp::AddEq, 2),
+            (Plus, ..) => (BinaryOp::Add, 1),
 
-                if self.lookahead().kind == TokenKind::Ge {
-                    self.eat();
-                    return Some(BinaryOp::Ref);
-                }
+            (Minus, Equal, _) => (BinaryOp::SubEq, 2),
+            (Minus, Ge, _) => (BinaryOp::Ref, 2),
+            (Minus, ..) => (BinaryOp::Sub, 1),
 
-                Some(BinaryOp::Sub)
-            }
+            (Star, Equal, _) => (BinaryOp::MulEq, 2),
+            (Star, ..) => (BinaryOp::Mul, 1),
 
-            TokenKind::Star => {
-                self.eat();
+            (Slash, Equal, _) => (BinaryOp::DivEq, 2),
+            (Slash, ..) => (BinaryOp::Div, 1),
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::MulEq);
-                }
+            (Percent, Equal, _) => (BinaryOp::ModEq, 2),
+            (Percent, ..) => (BinaryOp::Mod, 1),
 
-                Some(BinaryOp::Mul)
-            }
+            (And, And, _) => (BinaryOp::LogicalAnd, 2),
+            (And, Equal, _) => (BinaryOp::AndEq, 2),
+            (And, ..) => (BinaryOp::And, 1),
 
-            TokenKind::Slash => {
-                self.eat();
+            (Or, Or, _) => (BinaryOp::LogicalOr, 2),
+            (Or, Equal, _) => (BinaryOp::OrEq, 2),
+            (Or, ..) => (BinaryOp::Or, 1),
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::DivEq);
-                }
+            (Caret, Equal, _) => (BinaryOp::XorEq, 2),
+            (Caret, ..) => (BinaryOp::Xor, 1),
 
-                Some(BinaryOp::Div)
-            }
+            (Not, ..) => (BinaryOp::Not, 1),
 
-            TokenKind::Percent => {
-                self.eat();
+            (Le, Le, Equal) => (BinaryOp::LshEq, 3),
+            (Le, Le, _) => (BinaryOp::Lsh, 2),
+            (Le, Equal, _) => (BinaryOp::Lte, 2),
+            (Le, Minus, _) => (BinaryOp::Move, 2),
+            (Le, ..) => (BinaryOp::Lt, 1),
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::ModEq);
-                }
+            (Ge, Ge, Equal) => (BinaryOp::RshEq, 3),
+            (Ge, Ge, _) => (BinaryOp::Rsh, 2),
+            (Ge, Equal, _) => (BinaryOp::Gte, 2),
+            (Ge, ..) => (BinaryOp::Gt, 1),
 
-                Some(BinaryOp::Mod)
-            }
+            (Exclamation, Equal, _) => (BinaryOp::Ne, 2),
+            (Exclamation, ..) => (BinaryOp::LogicalNot, 1),
 
-            TokenKind::And => {
-                self.eat();
+            (Equal, ..) => (BinaryOp::Assign, 1),
 
-                match self.lookahead().kind {
-                    TokenKind::And => {
-                        self.eat();
-                        Some(BinaryOp::LogicalAnd)
-                    }
+            (Colon, Colon, _) => (BinaryOp::Scope, 2),
+            (Colon, ..) => (BinaryOp::Is, 1),
 
-                    TokenKind::Equal => {
-                        self.eat();
-                        Some(BinaryOp::AndEq)
-                    }
+            _ => return None,
+        };
 
-                    _ => Some(BinaryOp::And),
-                }
-            }
+        Some(op)
+    }
 
-            TokenKind::Or => {
-                self.eat();
+    /// Eat one of "=", "<-" and "->".
+    fn eat_behaviour(&mut self) -> Option<Behaviour> {
+        for x in ["`=`", "`<-`", "`->`"] {
+            self.expect(x);
+        }
 
-                match self.lookahead().kind {
-                    TokenKind::Or => {
-                        self.eat();
-                        Some(BinaryOp::LogicalOr)
-                    }
+        let behaviour = match self.peek_op()? {
+            (BinaryOp::Assign, _) => Behaviour::Copy,
+            (BinaryOp::Ref, _) => Behaviour::Ref,
+            (BinaryOp::Move, _) => Behaviour::Move,
+            _ => return None,
+        };
 
-                    TokenKind::Equal => {
-                        self.eat();
-                        Some(BinaryOp::OrEq)
-                    }
+        self.next_tok_is_op();
+        Some(behaviour)
+    }
 
-                    _ => Some(BinaryOp::Or),
-                }
-            }
+    fn next_tok_is_op(&mut self) -> Option<BinaryOp> {
+        let (op, len) = self.peek_op()?;
 
-            TokenKind::Caret => {
-                self.eat();
+        for _ in 0..len {
+            self.eat();
+        }
 
-                if self.lookahead().kind == TokenKind::Equal {
-                    self.eat();
-                    return Some(BinaryOp::XorEq);
-                }
+        Some(op)
+    }
 
-                Some(BinaryOp::Xor)
-            }
+    /// Parse the next statement, check `has_content` for the end of file first.
+    pub fn parse(&mut self) -> Result<Stmt, String> {
+        self.parse_stmt()
+    }
 
-            TokenKind::Not => {
-                self.eat();
-                Some(BinaryOp::Not)
-            }
+    /// Parse the statements till the end of file, the syntax errors are
+    /// collected in `errors` and leave `Statement::Error` in the module.
+    pub fn parse_module(&mut self) -> Module {
+        let start = self.offset;
+        let mut items = Vec::new();
 
-            TokenKind::Le => {
-                self.eat();
+        while self.has_content() {
+            items.push(self.parse_stmt_or_error());
+        }
 
-                match self.lookahead().kind {
-                    TokenKind::Le => {
-                        self.eat();
+        let end = self.lookahead().span.end;
 
-                        if self.lookahead().kind == TokenKind::Equal {
-                            self.eat();
-                            return Some(BinaryOp::LshEq);
-                        }
+        Module {
+            items,
+            exprs: std::mem::take(&mut self.exprs),
+            span: Span { start, end },
+        }
+    }
 
-                        Some(BinaryOp::Lsh)
-                    }
+    /// The lexical diagnostics collected so far.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        self.lex.diagnostics()
+    }
 
-                    TokenKind::Equal => {
-                        self.eat();
-                        Some(BinaryOp::Lte)
-                    }
+    /// The syntax errors collected so far.
+    pub fn errors(&self) -> &Diagnostics {
+        &self.errors
+    }
 
-                    TokenKind::Minus => {
-                        self.eat();
-                        Some(BinaryOp::Move)
-                    }
+    /// The warnings about the declarations, like the confusable identifiers.
+    pub fn lints(&self) -> &Diagnostics {
+        self.confusables.lints()
+    }
 
-                    _ => Some(BinaryOp::Lt),
-                }
-            }
+    /// The declarations and the scopes met so far.
+    pub fn scoping(&self) -> &[Scoping] {
+        &self.scoping
+    }
 
-            TokenKind::Ge => {
-                self.eat();
+    /// Move out what has been collected since the last call.
+    pub(crate) fn take_collected(&mut self) -> Collected {
+        Collected {
+            lexical: std::mem::take(self.lex.diagnostics_mut()),
+            errors: std::mem::take(&mut self.errors),
+            exprs: std::mem::take(&mut self.exprs),
+            positions: std::mem::take(&mut self.positions),
+            scoping: std::mem::take(&mut self.scoping),
+        }
+    }
 
-                match self.lookahead().kind {
-                    TokenKind::Ge => {
-                        self.eat();
+    /// The byte offset after the last consumed token.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
 
-                        if self.lookahead().kind == TokenKind::Equal {
-                            self.eat();
-                            return Some(BinaryOp::RshEq);
-                        }
+    /// Where the comments, statements and literals parsed so far are.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
 
-                        Some(BinaryOp::Rsh)
-                    }
+    /// Give the nodes the ids from `ids` on, instead of from 0.
+    pub fn with_node_ids(mut self, ids: NodeIds) -> Self {
+        self.ids = ids;
+        self
+    }
 
-                    TokenKind::Equal => {
-                        self.eat();
-                        Some(BinaryOp::Gte)
-                    }
+    /// The ids given to the nodes parsed so far.
+    pub fn node_ids(&self) -> &NodeIds {
+        &self.ids
+    }
 
-                    _ => Some(BinaryOp::Gt),
-                }
+    /// The expressions parsed so far, which `parse_module` moves to the module.
+    pub fn exprs(&self) -> &Exprs {
+        &self.exprs
+    }
+
+    /// The line and column of the first token of each node.
+    pub fn positions(&self) -> &HashMap<NodeId, (usize, usize)> {
+        &self.positions
+    }
+
+    /// The line and column of the current token.
+    fn position(&mut self) -> (usize, usize) {
+        let x = self.lookahead();
+        (x.line, x.column)
+    }
+
+    fn expr(&mut self, at: (usize, usize), kind: Expression) -> ExprId {
+        let x = Expr::new(self.ids.next_id(), kind);
+        self.positions.insert(x.id, at);
+        self.exprs.alloc(x)
+    }
+
+    fn stmt(&mut self, at: (usize, usize), kind: Statement) -> Stmt {
+        let x = Stmt::new(self.ids.next_id(), kind);
+        self.positions.insert(x.id, at);
+        x
+    }
+
+    pub fn source(&self) -> &S {
+        &self.lex
+    }
+
+    /// Run `f` in a new scope.
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scope(Scoping::Enter);
+        let x = f(self);
+        self.scope(Scoping::Leave);
+        x
+    }
+
+    fn declare(&mut self, name: &str, line: usize, column: usize) {
+        self.scope(Scoping::Declare {
+            name: name.into(),
+            line,
+            column,
+        });
+    }
+
+    fn scope(&mut self, x: Scoping) {
+        self.confusables.apply(&x);
+        self.scoping.push(x);
+    }
+
+    /// Report a syntax error at the current token, where the parsing fails.
+    fn report(&mut self, message: String) {
+        let (line, column) = (self.lookahead().line, self.lookahead().column);
+        self.errors.error(line, column, message);
+        self.expected.clear();
+    }
+
+    /// Skip the tokens till the next `;`, the `}` of the enclosing block, or a
+    /// statement keyword, where the parsing can go on. The blocks in between
+    /// are skipped as a whole.
+    fn synchronize(&mut self) {
+        let mut depth = 0usize;
+
+        loop {
+            if depth == 0 && self.at_stmt_keyword() {
+                return;
             }
 
-            TokenKind::Exclamation => {
-                self.eat();
+            match self.lookahead().kind {
+                TokenKind::Eof => return,
+                TokenKind::Semicolon if depth == 0 => return,
+                TokenKind::RightBrace if depth == 0 => return,
 
-                if self.lookahead().kind == TokenKind::Equal {
+                TokenKind::RightBrace => {
                     self.eat();
-                    return Some(BinaryOp::Ne);
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return;
+                    }
                 }
 
-                Some(BinaryOp::LogicalNot)
-            }
+                TokenKind::LeftBrace => {
+                    self.eat();
+                    depth += 1;
+                }
 
-            TokenKind::Equal => {
-                self.eat();
-                Some(BinaryOp::Assign)
+                _ => self.eat(),
             }
+        }
+    }
 
-            TokenKind::Colon => {
-                self.eat();
+    fn at_stmt_keyword(&mut self) -> bool {
+        matches!(
+            self.keyword(),
+            Some(KeyWord::FN | KeyWord::LET | KeyWord::RETURN)
+        )
+    }
 
-                if self.lookahead().kind == TokenKind::Colon {
+    /// Parse a statement, or report the error and skip it.
+    pub fn parse_stmt_or_error(&mut self) -> Stmt {
+        let start = self.offset;
+        let at = self.position();
+
+        match self.parse_stmt() {
+            Ok(x) => x,
+
+            Err(e) => {
+                self.report(e);
+
+                // always move on, a stray token would be met again otherwise.
+                if self.offset == start {
                     self.eat();
-                    return Some(BinaryOp::Scope);
                 }
 
-                Some(BinaryOp::Is)
-            }
+                self.synchronize();
 
-            TokenKind::Eof => None,
+                if self.lookahead().kind == TokenKind::Semicolon {
+                    self.eat();
+                }
 
-            _ => None,
+                self.stmt(at, Statement::Error)
+            }
         }
     }
 
-    pub fn parse(&mut self) -> Result<Statement, String> {
-        self.parse_stmt()
+    /// Parse an expression, or report the error and skip the rest of the statement.
+    fn parse_expr_or_error(&mut self) -> ExprId {
+        let at = self.position();
+
+        match self.parse_expr() {
+            Ok(x) => x,
+
+            Err(e) => {
+                self.report(e);
+                self.synchronize();
+                self.expr(at, Expression::Error)
+            }
+        }
     }
 
-    pub fn has_content(&self) -> bool {
+    pub fn has_content(&mut self) -> bool {
         self.lookahead().kind != TokenKind::Eof
     }
 
-    fn parse_literal(&mut self) -> Result<Expression, String> {
-        if let Some(x) = self.next() {
-            if let TokenKind::Literal { .. } = x.kind {
-                return Ok(make_lit(x));
+    fn parse_literal(&mut self) -> Result<ExprId, String> {
+        let x = self.next().unwrap();
+        self.layout.literals.push(x.span);
+
+        if let TokenKind::Literal {
+            kind: LiteralKind::InterpolatedStr { parts, err: None },
+            ..
+        } = x.kind
+        {
+            return self.parse_interpolated((x.line, x.column), parts);
+        }
+
+        let at = (x.line, x.column);
+        let lit = make_lit(x);
+        Ok(self.expr(at, lit))
+    }
+
+    fn parse_interpolated(
+        &mut self,
+        at: (usize, usize),
+        parts: Vec<StrPart>,
+    ) -> Result<ExprId, String> {
+        let mut res: Vec<InterpolatedPart> = Vec::new();
+
+        for part in parts {
+            match part {
+                StrPart::Str(x) => res.push(InterpolatedPart::Str(x)),
+
+                StrPart::Expr { src, line, column } => {
+                    let mut lex = Lexer::new(src.as_str());
+                    lex.line = line;
+                    lex.column = column;
+
+                    // the end of the embedded source is where the '}' is.
+                    // the embedded nodes are in the tree too, they take the next ids.
+                    let ids = std::mem::take(&mut self.ids);
+                    let mut parser = Parser::new(lex).with_node_ids(ids);
+                    parser.exprs = std::mem::take(&mut self.exprs);
+                    let expr = parser.parse_expr().and_then(|x| {
+                        parser.expect("`}`");
+
+                        match parser.has_content() {
+                            true => Err(parser.unexpected()),
+                            false => Ok(x),
+                        }
+                    });
+
+                    self.ids = std::mem::take(&mut parser.ids);
+                    self.exprs = std::mem::take(&mut parser.exprs);
+                    self.positions.extend(std::mem::take(&mut parser.positions));
+
+                    // the embedded source is lexed from where it is in the literal,
+                    // its diagnostics have the positions in the file already.
+                    let lexical = std::mem::take(&mut parser.lex.diagnostics);
+                    self.lex.diagnostics_mut().extend(lexical);
+                    self.errors.extend(std::mem::take(&mut parser.errors));
+                    // the expressions declare nothing, they have no lints.
+
+                    // report the error where it is, the rest of the literal is fine.
+                    let expr = match expr {
+                        Ok(x) => x,
+                        Err(e) => {
+                            let k = parser.lookahead();
+                            let at = (k.line, k.column);
+                            self.errors.error(
+                                at.0,
+                                at.1,
+                                format!("{e} in the embedded expression"),
+                            );
+                            self.expr(at, Expression::Error)
+                        }
+                    };
+
+                    res.push(InterpolatedPart::Expr(expr));
+                }
             }
         }
 
-        Err("Error: Expected <literal> but there is EOF.".into())
+        Ok(self.expr(at, Expression::Interpolated { parts: res }))
     }
 
-    fn parse_paren(&mut self) -> Result<Expression, String> {
+    fn parse_paren(&mut self) -> Result<ExprId, String> {
         self.eat();
         let expr = self.parse_expr()?;
 
-        if self.lookahead().kind == TokenKind::RightParen {
-            self.eat();
-            return Ok(expr);
+        if !self.check(&TokenKind::RightParen) {
+            return Err(self.unexpected());
         }
 
-        Err("Error: Unclosed '('.".into())
+        self.eat();
+        Ok(expr)
     }
 
-    fn parse_ident(&mut self) -> Result<Token, String> {
-        let x = self.next().unwrap();
-
-        if x.kind != TokenKind::Ident {
-            return Err("Expected an <identifier>.".into());
+    fn parse_ident(&mut self) -> Result<Token<'a>, String> {
+        if !self.check(&TokenKind::Ident) {
+            return Err(self.unexpected());
         }
 
-        Ok(x)
+        Ok(self.next().unwrap())
     }
 
-    fn parse_ident_or_call(&mut self) -> Result<Expression, String> {
-        let ident: Expression;
-        let mut args: Vec<Expression> = Vec::new();
+    fn parse_ident_or_call(&mut self) -> Result<ExprId, String> {
+        let mut args: Vec<ExprId> = Vec::new();
 
         let x = self.parse_ident()?;
+        let at = (x.line, x.column);
+
+        if !self.check(&TokenKind::LeftParen) {
+            // `true` and `false` are lexed as names, but they are literals.
+            let value = match x.content.as_ref() {
+                "true" => true,
+                "false" => false,
+                _ => return Ok(self.expr(at, make_ident(x.symbol()))),
+            };
 
-        ident = make_ident(x.content.clone());
-
-        if self.lookahead().kind != TokenKind::LeftParen {
-            return Ok(ident);
+            self.layout.literals.push(x.span);
+            return Ok(self.expr(at, Expression::Literal(Literal::Boolean(value))));
         }
 
         self.eat();
-        if self.lookahead().kind != TokenKind::RightParen {
+        if !self.check(&TokenKind::RightParen) {
             loop {
-                if let Ok(arg) = self.parse_expr() {
-                    args.push(arg);
-                } else {
-                    return Err("Error: arguments for a function must be an expression.".into());
-                }
+                args.push(self.parse_expr()?);
 
-                if self.lookahead().kind == TokenKind::RightParen {
+                if self.check(&TokenKind::RightParen) {
                     break;
                 }
 
-                if self.lookahead().kind != TokenKind::Comma {
-                    return Err("Error: Expected a ',' after the argument".into());
+                if !self.check(&TokenKind::Comma) {
+                    return Err(self.unexpected());
                 }
                 self.eat();
             }
@@ -380,136 +761,183 @@ impl Parser<'_> {
 
         self.eat();
 
-        Ok(Expression::FuncCall {
-            ident: x.content,
-            args,
-        })
+        Ok(self.expr(
+            at,
+            Expression::FuncCall {
+                ident: x.symbol(),
+                args,
+            },
+        ))
     }
 
-    pub fn parse_primary(&mut self) -> Result<Expression, String> {
+    pub fn parse_primary(&mut self) -> Result<ExprId, String> {
+        for x in ["<literal>", "<identifier>", "`(`"] {
+            self.expect(x);
+        }
+
         match self.lookahead().kind {
             TokenKind::Literal { .. } => self.parse_literal(),
-            TokenKind::Ident { .. } => self.parse_ident_or_call(),
+            TokenKind::Ident => self.parse_ident_or_call(),
             TokenKind::LeftParen => self.parse_paren(),
-            _ => Err("Error: Unexpected token: Expected <literal>, <identifier> or '('.".into()),
+            _ => Err(self.unexpected()),
         }
     }
 
-    pub fn parse_expr(&mut self) -> Result<Expression, String> {
+    pub fn parse_expr(&mut self) -> Result<ExprId, String> {
         let lhs = self.parse_primary();
         lhs.as_ref()?;
 
         self.parse_binary_expr(0, lhs.unwrap())
     }
 
-    fn parse_binary_expr(&mut self, in_p: i32, mut lhs: Expression) -> Result<Expression, String> {
+    /// The precedence of the operator at the current position, -1 if there is not a binary operator.
+    fn peek_precedence(&mut self) -> i32 {
+        self.peek_op()
+            .and_then(|(op, _)| PRECEDENCE.get(&op).copied())
+            .unwrap_or(-1)
+    }
+
+    fn parse_binary_expr(&mut self, in_p: i32, mut lhs: ExprId) -> Result<ExprId, String> {
         loop {
-            let tok = self.next_tok_is_op();
-            let p = {
-                if let Some(..) = tok {
-                    PRECEDENCE[&tok.clone().unwrap()]
-                } else {
-                    -1
-                }
-            };
+            let p = self.peek_precedence();
 
             if p < in_p {
+                if p < 0 {
+                    self.expect("<operator>");
+                }
+
                 return Ok(lhs);
             }
 
-            let mut rhs = self.parse_primary();
-            if rhs.is_err() {
-                return Err("Error: Expected <literal>, <identifier> or '(' after operator.".into());
-            }
+            let op = self.next_tok_is_op().unwrap();
 
-            let p2 = {
-                let temp = self.clone().next_tok_is_op();
+            let mut rhs = self.parse_primary();
+            rhs.as_ref()?;
 
-                if let Some(..) = temp {
-                    PRECEDENCE[&temp.unwrap()]
-                } else {
-                    -1
-                }
-            };
+            let p2 = self.peek_precedence();
 
             if p < p2 {
-                rhs = self.parse_binary_expr(in_p + 1, rhs.unwrap());
+                rhs = self.parse_binary_expr(p + 1, rhs.unwrap());
                 rhs.as_ref()?;
             }
 
-            lhs = Expression::Binary {
-                lhs: Box::new(lhs),
-                op: tok.unwrap(),
-                rhs: Box::new(rhs.unwrap()),
-            };
+            let at = self.positions[&self.exprs[lhs].id];
+            lhs = self.expr(
+                at,
+                Expression::Binary {
+                    lhs,
+                    op,
+                    rhs: rhs.unwrap(),
+                },
+            );
         }
     }
 
-    pub fn parse_stmt(&mut self) -> Result<Statement, String> {
+    pub fn parse_stmt(&mut self) -> Result<Stmt, String> {
         let mut result: Result<Statement, String>;
+        let start = self.lookahead().span.start;
+        let at = self.position();
+        let doc = self.docs.remove(&start);
 
-        if self.match_keyword(&KeyWord::FN).is_ok() {
+        // the nested statements come after this one.
+        let index = self.layout.stmts.len();
+        self.layout.stmts.push(Span { start, end: start });
+
+        if self.check_keyword(&KeyWord::FN) {
             self.eat();
             result = self.parse_func_decl();
-        } else if self.match_keyword(&KeyWord::LET).is_ok() {
+        } else if self.check_keyword(&KeyWord::LET) {
             self.eat();
             result = self.parse_var_decl();
-            if self.match_tok(&TokenKind::Semicolon).is_err() {
-                result = Err("There must be a ';' after a <statement>.".into());
-            }
-        } else if self.match_tok(&TokenKind::LeftBrace).is_ok() {
+            self.expect_semicolon(&mut result);
+        } else if self.check(&TokenKind::LeftBrace) {
             self.eat();
             result = self.parse_block();
-        } else if self.match_keyword(&KeyWord::RETURN).is_ok() {
+        } else if self.check_keyword(&KeyWord::RETURN) {
             self.eat();
             result = self.parse_return();
+            self.expect_semicolon(&mut result);
         } else {
-            if self.lookahead().kind == TokenKind::Eof {
-                return Ok(Statement::Eof);
-            }
-            result = Err("Error: Expected <statement>.".into());
+            result = Err(self.unexpected());
         }
 
         result.as_ref()?;
 
-        self.eat();
+        // only the declarations keep their docs, the others are dropped.
+        if let Ok(Statement::FuncDecl { doc: x, .. } | Statement::VariableDecl { doc: x, .. }) =
+            &mut result
+        {
+            *x = doc;
+        }
+
+        // the blocks have eaten their '}', the others end with a ';'.
+        if self.lookahead().kind == TokenKind::Semicolon
+            && !matches!(result, Ok(Statement::FuncDecl { .. } | Statement::Block(_)))
+        {
+            self.eat();
+        }
+
+        self.layout.stmts[index].end = self.offset;
+        let kind = result?;
+        Ok(self.stmt(at, kind))
+    }
+
+    /// The statement must end with a ';', unless the rest of it has been skipped for an error.
+    fn expect_semicolon(&mut self, result: &mut Result<Statement, String>) {
+        let skipped = match result {
+            Ok(Statement::VariableDecl { value: x, .. } | Statement::Return(x)) => {
+                matches!(self.exprs[*x].kind, Expression::Error)
+            }
+            _ => false,
+        };
 
-        result
+        if result.is_ok() && !skipped && !self.check(&TokenKind::Semicolon) {
+            *result = Err(self.unexpected());
+        }
     }
 
     pub fn parse_func_decl(&mut self) -> Result<Statement, String> {
         let name = self.parse_ident()?;
-        let mut params: Vec<(String, Behaviour, String)> = Vec::new();
+        self.declare(&name.content, name.line, name.column);
+
+        // the parameters are in the scope of the body.
+        let (params, body) = self.scoped(|this| this.parse_func_rest())?;
+
+        Ok(Statement::FuncDecl {
+            doc: None,
+            ident: name.symbol(),
+            params,
+            body,
+        })
+    }
 
-        if self.lookahead().kind != TokenKind::LeftParen {
-            return Err("Expected a '(' after the function name.".into());
+    /// The parameters and the body of a function.
+    #[allow(clippy::type_complexity)]
+    fn parse_func_rest(&mut self) -> Result<(Vec<(Symbol, Behaviour, Symbol)>, Block), String> {
+        let mut params: Vec<(Symbol, Behaviour, Symbol)> = Vec::new();
+
+        if !self.check(&TokenKind::LeftParen) {
+            return Err(self.unexpected());
         }
 
         self.eat(); // eat '('.
 
-        while self.lookahead().kind != TokenKind::RightParen {
-            if self.lookahead().kind == TokenKind::Comma {
+        while !self.check(&TokenKind::RightParen) {
+            if self.check(&TokenKind::Comma) {
                 self.eat(); // eat ',' between the parameters.
             }
 
             let param = {
                 let param_name = self.parse_ident()?;
-                let behaviour = self.next_tok_is_op();
-
-                if behaviour.is_none() {
-                    return Err("Expected '=', '->' or '<-' after the parameter name".into());
-                }
-
-                let bhv: Behaviour = match behaviour.unwrap() {
-                    BinaryOp::Assign => Behaviour::Copy,
-                    BinaryOp::Ref => Behaviour::Ref,
-                    BinaryOp::Move => Behaviour::Move,
-                    _ => return Err("Expected '=', '->' or '<-' after the parameter name".into()),
+                let bhv = match self.eat_behaviour() {
+                    Some(x) => x,
+                    None => return Err(self.unexpected()),
                 };
 
                 let ty = self.parse_ident()?;
+                self.declare(&param_name.content, param_name.line, param_name.column);
 
-                (param_name.content, bhv, ty.content)
+                (param_name.symbol(), bhv, ty.symbol())
             };
 
             params.push(param);
@@ -517,68 +945,42 @@ impl Parser<'_> {
 
         self.eat(); // eat ')'.
 
-        if self.lookahead().kind != TokenKind::LeftBrace {
-            return Err("Expected a block as function body after function signature.".into());
+        if !self.check(&TokenKind::LeftBrace) {
+            return Err(self.unexpected());
         }
 
         self.eat(); // eat '{'.
 
-        if let Ok(Statement::Block(body)) = self.parse_block() {
-            Ok(Statement::FuncDecl {
-                ident: name.content,
-                params,
-                body,
-            })
-        } else {
-            Err("Expected a block as function body after function signature.".into())
+        match self.parse_block()? {
+            Statement::Block(body) => Ok((params, body)),
+            _ => unreachable!(),
         }
     }
 
     // "let" ("mut") <ident>(":" <type: ident>) ({ "=" | "<-" | "->" } <expr>)
     pub fn parse_var_decl(&mut self) -> Result<Statement, String> {
         let mut mutable = false;
-        let ident: String;
-        let mut ty: String = "".into();
+        let mut ty = Symbol::intern("");
 
-        if self.match_keyword(&KeyWord::MUT).is_ok() {
+        if self.check_keyword(&KeyWord::MUT) {
             self.eat();
             mutable = true;
         }
 
-        if let Ok(Expression::Ident(x)) = self.parse_ident_or_call() {
-            ident = x;
-        } else {
-            return Err("Expected <identifier> after keyword 'let'.".into());
-        }
+        let name = self.parse_ident()?;
+        self.declare(&name.content, name.line, name.column);
+        let ident = name.symbol();
 
-        if self.match_tok(&TokenKind::Colon).is_ok() {
+        if self.check(&TokenKind::Colon) {
             self.eat();
-            if let Ok(Expression::Ident(x)) = self.parse_ident_or_call() {
-                ty = x;
-            } else {
-                return Err("Expected <type-name> after ':' in variable declaring.".into());
-            }
+            ty = self.parse_ident()?.symbol();
         }
 
-        if let Some(o) = self.next_tok_is_op() {
-            let behaviour = match o {
-                BinaryOp::Assign => Behaviour::Copy,
-                BinaryOp::Ref => Behaviour::Ref,
-                BinaryOp::Move => Behaviour::Move,
-                _ => {
-                    return Err("The assignment operator must be '=', '->' or '<-' ".into());
-                }
-            };
-
-            let rhs = self.parse_expr();
-
-            if rhs.is_err() {
-                return Err("Error: Value of the variable must be a expression.".into());
-            }
-
-            let value = rhs.unwrap();
+        if let Some(behaviour) = self.eat_behaviour() {
+            let value = self.parse_expr_or_error();
 
             Ok(Statement::VariableDecl {
+                doc: None,
                 ident,
                 ty,
                 mutable,
@@ -586,25 +988,29 @@ impl Parser<'_> {
                 value,
             })
         } else {
-            Err("Must initialize the variable when declare it.".into())
+            Err(self.unexpected())
         }
     }
 
     pub fn parse_return(&mut self) -> Result<Statement, String> {
-        let expr = self.parse_expr();
-
-        if expr.is_err() {
-            return Err("Error: Expected <expr> after keyword 'return'.".into());
-        }
+        let expr = self.parse_expr_or_error();
 
-        Ok(Statement::Return(expr.unwrap()))
+        Ok(Statement::Return(expr))
     }
 
     pub fn parse_block(&mut self) -> Result<Statement, String> {
+        self.scoped(|this| this.parse_block_stmts())
+    }
+
+    fn parse_block_stmts(&mut self) -> Result<Statement, String> {
         let mut block: Block = Block { block: Vec::new() };
 
-        while self.lookahead().kind != TokenKind::RightBrace {
-            let x = self.parse_stmt()?;
+        while !self.check(&TokenKind::RightBrace) {
+            if self.lookahead().kind == TokenKind::Eof {
+                return Err(self.unexpected());
+            }
+
+            let x = self.parse_stmt_or_error();
 
             block.block.push(x);
         }
@@ -613,3 +1019,22 @@ impl Parser<'_> {
         Ok(Statement::Block(block))
     }
 }
+
+/// How an expected token is listed in the syntax errors.
+fn describe(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Eof => "end of file".into(),
+        TokenKind::Ident => "<identifier>".into(),
+        TokenKind::Literal { .. } => "<literal>".into(),
+        x => format!("`{}`", x.to_string().trim_matches('\'')),
+    }
+}
+
+/// How the token found instead is shown in the syntax errors.
+fn describe_found(tok: &Token) -> String {
+    match tok.kind {
+        TokenKind::Eof => "end of file".into(),
+        TokenKind::Literal { .. } => "<literal>".into(),
+        _ => format!("`{}`", tok.content),
+    }
+}
@@ -0,0 +1,3 @@
+pub mod resolve;
+pub mod typeck;
+pub mod types;
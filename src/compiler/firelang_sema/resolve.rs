@@ -0,0 +1,210 @@
+use crate::compiler::firelang_lexer::symbol::Symbol;
+use crate::compiler::firelang_parser::ast::node::*;
+use crate::compiler::firelang_parser::ast::token::BinaryOp;
+use crate::compiler::firelang_parser::ast::visit::{self, Visitor};
+use crate::error_generator::generator::Diagnostics;
+use std::collections::HashMap;
+
+/// What a name refers to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Res {
+    /// A variable, by the id of its `let`.
+    Local(NodeId),
+    /// A parameter, by the id of its function and its index.
+    Param(NodeId, usize),
+    /// A function, by the id of its declaration.
+    Func(NodeId),
+}
+
+/// The result of the name resolution.
+#[derive(Debug, Default)]
+pub struct Resolutions {
+    /// The declarations, by the id of the `Ident` or the `FuncCall` using them.
+    pub names: HashMap<NodeId, Res>,
+    pub errors: Diagnostics,
+}
+
+/// Resolver Struct
+/// Find the declaration of every name used in a module.
+///
+/// The functions of a block are visible in the whole block, so they can call
+/// each other in any order, and the variables from their `let` on. A name in
+/// an inner scope shadows the same name outside.
+pub struct Resolver<'p> {
+    /// The positions of the nodes, from the parser.
+    positions: &'p HashMap<NodeId, (usize, usize)>,
+    scopes: Vec<HashMap<Symbol, Res>>,
+    out: Resolutions,
+}
+
+impl<'p> Resolver<'p> {
+    pub fn new(positions: &'p HashMap<NodeId, (usize, usize)>) -> Resolver<'p> {
+        Resolver {
+            positions,
+            scopes: Vec::new(),
+            out: Resolutions::default(),
+        }
+    }
+
+    pub fn resolve_module(mut self, module: &Module) -> Resolutions {
+        self.visit_module(module);
+        self.out
+    }
+
+    /// Run `f` in a new scope, with the functions declared in `stmts`.
+    fn scoped(&mut self, stmts: &[Stmt], f: impl FnOnce(&mut Self)) {
+        let scope = stmts
+            .iter()
+            .filter_map(|x| match &x.kind {
+                Statement::FuncDecl { ident, .. } => Some((*ident, Res::Func(x.id))),
+                _ => None,
+            })
+            .collect();
+
+        self.scopes.push(scope);
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: Symbol, res: Res) {
+        self.scopes.last_mut().unwrap().insert(name, res);
+    }
+
+    fn lookup(&self, name: Symbol) -> Option<Res> {
+        self.scopes.iter().rev().find_map(|x| x.get(&name)).copied()
+    }
+
+    /// Resolve the name used by the node `id`, `what` it is tells the error.
+    fn resolve(&mut self, id: NodeId, name: Symbol, what: &str) {
+        if let Some(x) = self.lookup(name) {
+            self.out.names.insert(id, x);
+            return;
+        }
+
+        let similar = self.similar(name);
+        let (line, column) = self.positions.get(&id).copied().unwrap_or_default();
+        let e = self.out.errors.error(
+            line,
+            column,
+            format!("cannot find {what} `{name}` in this scope"),
+        );
+
+        if let Some(x) = similar {
+            e.note(format!("did you mean `{x}`?"));
+        }
+    }
+
+    /// The visible name which `name` is most likely a typo of.
+    fn similar(&self, name: Symbol) -> Option<Symbol> {
+        let name = name.as_str();
+        // a third of the name may be wrong, like "lenght" for "length".
+        let max = (name.chars().count() / 3).max(1);
+
+        self.scopes
+            .iter()
+            .flat_map(|x| x.keys())
+            .map(|x| (edit_distance(name, x.as_str()), *x))
+            .filter(|(d, _)| *d <= max)
+            .min()
+            .map(|(_, x)| x)
+    }
+
+    /// The names in a path like `io::println` are in other modules, which are
+    /// not resolved yet, only the arguments are.
+    fn visit_path(&mut self, exprs: &Exprs, expr: &Expr) {
+        match &expr.kind {
+            Expression::Ident(_) => (),
+
+            Expression::FuncCall { args, .. } => {
+                for x in args {
+                    self.visit_expr(exprs, &exprs[*x]);
+                }
+            }
+
+            Expression::Binary {
+                lhs,
+                op: BinaryOp::Scope,
+                rhs,
+            } => {
+                self.visit_path(exprs, &exprs[*lhs]);
+                self.visit_path(exprs, &exprs[*rhs]);
+            }
+
+            _ => self.visit_expr(exprs, expr),
+        }
+    }
+}
+
+impl Visitor for Resolver<'_> {
+    fn visit_module(&mut self, module: &Module) {
+        self.scoped(&module.items, |this| visit::walk_module(this, module));
+    }
+
+    fn visit_stmt(&mut self, exprs: &Exprs, stmt: &Stmt) {
+        match &stmt.kind {
+            // the parameters are in a scope around the body.
+            Statement::FuncDecl { params, body, .. } => self.scoped(&[], |this| {
+                for (i, (name, ..)) in params.iter().enumerate() {
+                    this.declare(*name, Res::Param(stmt.id, i));
+                }
+
+                this.visit_block(exprs, body);
+            }),
+
+            // the value of `let x = x + 1;` uses the `x` before.
+            Statement::VariableDecl { ident, value, .. } => {
+                self.visit_expr(exprs, &exprs[*value]);
+                self.declare(*ident, Res::Local(stmt.id));
+            }
+
+            _ => visit::walk_stmt(self, exprs, stmt),
+        }
+    }
+
+    fn visit_block(&mut self, exprs: &Exprs, block: &Block) {
+        self.scoped(&block.block, |this| visit::walk_block(this, exprs, block));
+    }
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: &Expr) {
+        match &expr.kind {
+            Expression::Ident(x) => self.resolve(expr.id, *x, "value"),
+
+            Expression::FuncCall { ident, .. } => self.resolve(expr.id, *ident, "function"),
+
+            Expression::Binary {
+                op: BinaryOp::Scope,
+                ..
+            } => return self.visit_path(exprs, expr),
+
+            _ => (),
+        }
+
+        visit::walk_expr(self, exprs, expr);
+    }
+}
+
+/// The edit distance of the characters of `a` and `b`, swapping two adjacent
+/// characters is one edit like the others.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // the distances from the prefixes of `a` of length i - 2, i - 1 and i.
+    let mut before: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut cur = vec![i; b.len() + 1];
+
+        for j in 1..=b.len() {
+            let replace = prev[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = replace.min(prev[j] + 1).min(cur[j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(before[j - 2] + 1);
+            }
+        }
+
+        before = std::mem::replace(&mut prev, cur);
+    }
+
+    prev[b.len()]
+}
@@ -0,0 +1,556 @@
+use super::resolve::{Res, Resolutions};
+use super::types::Type;
+use crate::compiler::firelang_lexer::symbol::Symbol;
+use crate::compiler::firelang_parser::ast::node::*;
+use crate::compiler::firelang_parser::ast::token::{BinaryOp, Literal};
+use crate::error_generator::generator::{Diagnostic, Diagnostics};
+use std::collections::HashMap;
+
+/// The result of the type checking.
+#[derive(Debug, Default)]
+pub struct Types {
+    /// The types of the expressions, and of the variables by the id of their `let`.
+    pub types: HashMap<NodeId, Type>,
+    pub errors: Diagnostics,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum State {
+    Unchecked,
+    Checking,
+    Checked,
+}
+
+/// The types of a function, by the id of its declaration.
+#[derive(Debug)]
+struct Signature {
+    params: Vec<Type>,
+    /// The type of the first `return` and its id, the others must return the
+    /// same type. The function returns `()` if it has none.
+    ret: Option<(Type, NodeId)>,
+    state: State,
+}
+
+/// TypeChecker Struct
+/// Give a type to every expression of a module, and check that the operands,
+/// the arguments and the returned values have the types they must have.
+///
+/// The functions are checked before the first call to them, so their return
+/// type is known wherever they are declared.
+///
+/// The type of an expression is inferred from its operands, and the literals
+/// without suffix take the type expected where they are, like the annotation
+/// of a `let`, a parameter or the other operand, else `int32` or `float32`.
+/// A `let` without annotation has the type of its value.
+pub struct TypeChecker<'m> {
+    /// The positions of the nodes, from the parser.
+    positions: &'m HashMap<NodeId, (usize, usize)>,
+    names: &'m Resolutions,
+    /// The expressions of the module being checked.
+    exprs: &'m Exprs,
+    fns: HashMap<NodeId, &'m Stmt>,
+    sigs: HashMap<NodeId, Signature>,
+    /// The function whose body is being checked.
+    current: Option<NodeId>,
+    out: Types,
+}
+
+impl<'m> TypeChecker<'m> {
+    pub fn new(
+        positions: &'m HashMap<NodeId, (usize, usize)>,
+        names: &'m Resolutions,
+    ) -> TypeChecker<'m> {
+        TypeChecker {
+            positions,
+            names,
+            exprs: Exprs::empty(),
+            fns: HashMap::new(),
+            sigs: HashMap::new(),
+            current: None,
+            out: Types::default(),
+        }
+    }
+
+    pub fn check_module(mut self, module: &'m Module) -> Types {
+        self.exprs = &module.exprs;
+        self.declare_fns(&module.items);
+
+        for x in &module.items {
+            self.check_stmt(x);
+        }
+
+        self.out
+    }
+
+    fn expr(&self, id: ExprId) -> &'m Expr {
+        &self.exprs[id]
+    }
+
+    fn error(&mut self, id: NodeId, message: String) -> &mut Diagnostic {
+        let (line, column) = self.positions.get(&id).copied().unwrap_or_default();
+        self.out.errors.error(line, column, message)
+    }
+
+    /// Report that the node `id` has the type `found` instead of `expected`.
+    fn expect(&mut self, expected: Type, found: Type, id: NodeId) {
+        if !expected.accepts(found) {
+            self.error(
+                id,
+                format!("mismatched types: expected `{expected}`, found `{found}`"),
+            );
+        }
+    }
+
+    /// The type named by `name`, `at` is the node which names it.
+    fn named_type(&mut self, name: Symbol, at: NodeId) -> Type {
+        Type::from_name(name).unwrap_or_else(|| {
+            self.error(at, format!("cannot find type `{name}` in this scope"));
+            Type::Error
+        })
+    }
+
+    /// Collect the signatures of the functions in `stmts` and in their blocks.
+    fn declare_fns(&mut self, stmts: &'m [Stmt]) {
+        for x in stmts {
+            match &x.kind {
+                Statement::Block(block) => self.declare_fns(&block.block),
+
+                Statement::FuncDecl { params, body, .. } => {
+                    let params = params
+                        .iter()
+                        .map(|(_, _, ty)| self.named_type(*ty, x.id))
+                        .collect();
+
+                    self.fns.insert(x.id, x);
+                    self.sigs.insert(
+                        x.id,
+                        Signature {
+                            params,
+                            ret: None,
+                            state: State::Unchecked,
+                        },
+                    );
+
+                    self.declare_fns(&body.block);
+                }
+
+                Statement::If { block, els, .. } => {
+                    self.declare_fns(&block.block);
+
+                    if let Some(els) = els {
+                        self.declare_fns(&els.block);
+                    }
+                }
+
+                _ => (),
+            }
+        }
+    }
+
+    fn check_fn(&mut self, id: NodeId) {
+        if self.sigs[&id].state != State::Unchecked {
+            return;
+        }
+
+        let Statement::FuncDecl { body, .. } = &self.fns[&id].kind else {
+            unreachable!()
+        };
+
+        self.sigs.get_mut(&id).unwrap().state = State::Checking;
+        let outer = self.current.replace(id);
+
+        for x in &body.block {
+            self.check_stmt(x);
+        }
+
+        self.current = outer;
+        self.sigs.get_mut(&id).unwrap().state = State::Checked;
+    }
+
+    /// The return type of the function `id`, unknown in its own body before
+    /// its first `return`.
+    fn return_type(&mut self, id: NodeId) -> Type {
+        self.check_fn(id);
+
+        let sig = &self.sigs[&id];
+        match (sig.ret, sig.state) {
+            (Some((x, _)), _) => x,
+            (None, State::Checked) => Type::Unit,
+            (None, _) => Type::Error,
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &'m Stmt) {
+        match &stmt.kind {
+            Statement::Block(x) => {
+                for x in &x.block {
+                    self.check_stmt(x);
+                }
+            }
+
+            Statement::FuncDecl { .. } => self.check_fn(stmt.id),
+
+            // the type of a `let` is fixed here, the later uses don't change it.
+            Statement::VariableDecl { ty, value, .. } => {
+                let value = self.expr(*value);
+                let ty = if ty.is_empty() {
+                    let ty = self.check_expr(value, None);
+                    if ty == Type::Unit {
+                        self.no_value(value);
+                    }
+                    ty
+                } else {
+                    let ty = self.named_type(*ty, stmt.id);
+                    let found = self.check_expr(value, Some(ty));
+                    self.expect(ty, found, value.id);
+                    ty
+                };
+
+                self.out.types.insert(stmt.id, ty);
+            }
+
+            Statement::Return(x) => {
+                let x = self.expr(*x);
+                let expected = self.current.and_then(|f| self.sigs[&f].ret).map(|x| x.0);
+                let found = self.check_expr(x, expected);
+                self.check_return(stmt.id, x, found);
+            }
+
+            Statement::If { cond, block, els } => {
+                let cond = self.expr(*cond);
+                let found = self.check_expr(cond, Some(Type::Bool));
+                self.expect(Type::Bool, found, cond.id);
+
+                for x in block.block.iter().chain(els.iter().flat_map(|x| &x.block)) {
+                    self.check_stmt(x);
+                }
+            }
+
+            Statement::Error => (),
+        }
+    }
+
+    /// The first `return` of a function sets its return type, the `return`s
+    /// of a script are not checked.
+    fn check_return(&mut self, id: NodeId, value: &Expr, found: Type) {
+        let Some(f) = self.current else {
+            return;
+        };
+
+        let at = match value.kind {
+            Expression::None => id,
+            _ => value.id,
+        };
+
+        match self.sigs[&f].ret {
+            None if found != Type::Error => self.sigs.get_mut(&f).unwrap().ret = Some((found, id)),
+
+            Some((expected, first)) if !expected.accepts(found) => {
+                let (line, _) = self.positions.get(&first).copied().unwrap_or_default();
+                self.error(
+                    at,
+                    format!("mismatched types: expected `{expected}`, found `{found}`"),
+                )
+                .note(format!(
+                    "the return type is set by the `return` at line {line}"
+                ));
+            }
+
+            _ => (),
+        }
+    }
+
+    /// Report the `let` of a value of type `()`, like the call of a function
+    /// without `return`.
+    fn no_value(&mut self, value: &Expr) {
+        let ident = match (&value.kind, self.names.names.get(&value.id)) {
+            (Expression::FuncCall { ident, .. }, Some(Res::Func(f)))
+                if self.sigs[f].ret.is_none() =>
+            {
+                Some(*ident)
+            }
+            _ => None,
+        };
+
+        let e = self.error(value.id, "expected a value, found `()`".into());
+        if let Some(ident) = ident {
+            e.note(format!("`{ident}` has no `return`, so it returns `()`"));
+        }
+    }
+
+    /// Whether the type of `expr` is inferred from where it is, like `1` or
+    /// `1 + 2`.
+    fn is_unsuffixed(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            Expression::Literal(x) => {
+                matches!(x, Literal::UnsuffixedInt(_) | Literal::UnsuffixedFloat(_))
+            }
+
+            Expression::Binary { lhs, op, rhs } => match op {
+                BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Mod
+                | BinaryOp::And
+                | BinaryOp::Or
+                | BinaryOp::Xor => {
+                    self.is_unsuffixed(self.expr(*lhs)) && self.is_unsuffixed(self.expr(*rhs))
+                }
+                BinaryOp::Lsh | BinaryOp::Rsh => self.is_unsuffixed(self.expr(*lhs)),
+                _ => false,
+            },
+
+            _ => false,
+        }
+    }
+
+    /// The type of `expr`, `expected` is the type it must have where it is,
+    /// which the literals without suffix take.
+    fn check_expr(&mut self, expr: &'m Expr, expected: Option<Type>) -> Type {
+        let ty = match &expr.kind {
+            Expression::Literal(Literal::UnsuffixedInt(n)) => {
+                let ty = match expected {
+                    Some(ty) if ty.is_integer() => ty,
+                    _ => Type::Int32,
+                };
+
+                if !ty.fits(*n) {
+                    self.error(expr.id, format!("literal out of range for `{ty}`"));
+                }
+
+                ty
+            }
+
+            Expression::Literal(Literal::UnsuffixedFloat(_)) => match expected {
+                Some(ty) if ty.is_float() => ty,
+                _ => Type::Float32,
+            },
+
+            Expression::Literal(x) => Type::of(x),
+
+            Expression::Binary { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.expr(*lhs), self.expr(*rhs));
+                self.check_binary(expr.id, lhs, op, rhs, expected)
+            }
+
+            Expression::Ident(x) => match self.names.names.get(&expr.id) {
+                Some(Res::Local(id)) => self.out.types.get(id).copied().unwrap_or(Type::Error),
+
+                Some(Res::Param(f, i)) => self.sigs[f].params[*i],
+
+                Some(Res::Func(_)) => {
+                    self.error(expr.id, format!("expected a value, found function `{x}`"));
+                    Type::Error
+                }
+
+                // the resolver has reported it.
+                None => Type::Error,
+            },
+
+            Expression::FuncCall { ident, args } => self.check_call(expr.id, *ident, args),
+
+            Expression::Interpolated { parts } => {
+                for x in parts {
+                    if let InterpolatedPart::Expr(x) = x {
+                        self.check_expr(self.expr(*x), None);
+                    }
+                }
+
+                Type::Str
+            }
+
+            Expression::None => Type::Unit,
+
+            Expression::Error => Type::Error,
+        };
+
+        self.out.types.insert(expr.id, ty);
+        ty
+    }
+
+    fn check_call(&mut self, id: NodeId, ident: Symbol, args: &'m [ExprId]) -> Type {
+        let params = match self.names.names.get(&id) {
+            Some(Res::Func(f)) => self.sigs[f].params.clone(),
+            _ => Vec::new(),
+        };
+
+        let found: Vec<Type> = args
+            .iter()
+            .enumerate()
+            .map(|(i, x)| self.check_expr(self.expr(*x), params.get(i).copied()))
+            .collect();
+
+        let f = match self.names.names.get(&id) {
+            Some(Res::Func(f)) => *f,
+
+            Some(_) => {
+                self.error(id, format!("`{ident}` is not a function"));
+                return Type::Error;
+            }
+
+            None => return Type::Error,
+        };
+
+        if params.len() != args.len() {
+            self.error(
+                id,
+                format!(
+                    "`{ident}` takes {} but {} given",
+                    count(params.len(), "argument"),
+                    match args.len() {
+                        1 => "1 was".to_string(),
+                        n => format!("{n} were"),
+                    }
+                ),
+            );
+        }
+
+        for ((expected, found), x) in params.into_iter().zip(found).zip(args) {
+            self.expect(expected, found, self.expr(*x).id);
+        }
+
+        self.return_type(f)
+    }
+
+    fn check_binary(
+        &mut self,
+        id: NodeId,
+        lhs: &'m Expr,
+        op: &BinaryOp,
+        rhs: &'m Expr,
+        expected: Option<Type>,
+    ) -> Type {
+        // `a += b` is checked like `a + b`, but has no value.
+        let (base, assign) = match compound_base(op) {
+            Some(x) => (x, true),
+            None => (op.clone(), false),
+        };
+
+        // the operands of the arithmetic have the type of the result.
+        let outer = match base {
+            BinaryOp::Add
+            | BinaryOp::Sub
+            | BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Mod
+            | BinaryOp::And
+            | BinaryOp::Or
+            | BinaryOp::Xor
+            | BinaryOp::Lsh
+            | BinaryOp::Rsh
+                if !assign =>
+            {
+                expected
+            }
+            _ => None,
+        };
+
+        // an operand without a type of its own takes the type of the other one,
+        // but the shift amount is any integer.
+        let (l, r) = if matches!(base, BinaryOp::Lsh | BinaryOp::Rsh) {
+            (self.check_expr(lhs, outer), self.check_expr(rhs, None))
+        } else if self.is_unsuffixed(lhs) && !self.is_unsuffixed(rhs) {
+            let r = self.check_expr(rhs, outer);
+            (self.check_expr(lhs, Some(r)), r)
+        } else {
+            let l = self.check_expr(lhs, outer);
+            (l, self.check_expr(rhs, Some(l)))
+        };
+
+        let result = match base {
+            BinaryOp::Lt
+            | BinaryOp::Lte
+            | BinaryOp::Gt
+            | BinaryOp::Gte
+            | BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::LogicalAnd
+            | BinaryOp::LogicalOr => Type::Bool,
+            BinaryOp::Assign => Type::Unit,
+            _ if assign => Type::Unit,
+            _ => l,
+        };
+
+        let supported = |x: Type| match base {
+            BinaryOp::Add => x.is_numeric() || x == Type::Str,
+            BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => x.is_numeric(),
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => x.is_integer() || x == Type::Bool,
+            BinaryOp::Lsh | BinaryOp::Rsh => x.is_integer(),
+            BinaryOp::LogicalAnd | BinaryOp::LogicalOr => x == Type::Bool,
+            BinaryOp::Lt | BinaryOp::Lte | BinaryOp::Gt | BinaryOp::Gte => {
+                x.is_numeric() || x == Type::Char || x == Type::Str
+            }
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Assign => x != Type::Unit,
+            _ => false,
+        };
+
+        match base {
+            // the paths are in other modules, which are not checked yet.
+            BinaryOp::Scope => return Type::Error,
+
+            BinaryOp::Not
+            | BinaryOp::LogicalNot
+            | BinaryOp::Ref
+            | BinaryOp::Move
+            | BinaryOp::Is => {
+                self.error(id, format!("`{op}` is not a binary operator"));
+                return Type::Error;
+            }
+
+            _ => (),
+        }
+
+        if l == Type::Error || r == Type::Error {
+            return result;
+        }
+
+        if !supported(l) {
+            self.error(id, format!("cannot apply `{op}` to `{l}`"));
+            return if result == l { Type::Error } else { result };
+        }
+
+        // the shift amount can be any integer.
+        if matches!(base, BinaryOp::Lsh | BinaryOp::Rsh) {
+            if !r.is_integer() {
+                self.error(
+                    rhs.id,
+                    format!("mismatched types: expected an integer, found `{r}`"),
+                );
+            }
+        } else if l != r {
+            self.error(
+                rhs.id,
+                format!("mismatched types: expected `{l}`, found `{r}`"),
+            )
+            .note(format!("the operands of `{op}` must have the same type"));
+        }
+
+        result
+    }
+}
+
+/// The operator of a compound assignment, like `+` of `+=`.
+fn compound_base(op: &BinaryOp) -> Option<BinaryOp> {
+    Some(match op {
+        BinaryOp::AddEq => BinaryOp::Add,
+        BinaryOp::SubEq => BinaryOp::Sub,
+        BinaryOp::MulEq => BinaryOp::Mul,
+        BinaryOp::DivEq => BinaryOp::Div,
+        BinaryOp::ModEq => BinaryOp::Mod,
+        BinaryOp::AndEq => BinaryOp::And,
+        BinaryOp::OrEq => BinaryOp::Or,
+        BinaryOp::XorEq => BinaryOp::Xor,
+        BinaryOp::LshEq => BinaryOp::Lsh,
+        BinaryOp::RshEq => BinaryOp::Rsh,
+        _ => return None,
+    })
+}
+
+/// "1 argument" or "2 arguments".
+fn count(n: usize, what: &str) -> String {
+    match n {
+        1 => format!("1 {what}"),
+        n => format!("{n} {what}s"),
+    }
+}
@@ -0,0 +1,160 @@
+use crate::compiler::firelang_lexer::symbol::Symbol;
+use crate::compiler::firelang_parser::ast::token::Literal;
+use std::fmt::{Display, Formatter};
+
+/// The primitive types.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Type {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Int128,
+
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    UInt128,
+
+    Float32,
+    Float64,
+
+    Char,
+    Bool,
+    Str,
+    Bytes,
+
+    /// The type of `return;`, of the assignments and of the functions
+    /// returning nothing.
+    Unit,
+
+    /// The type of a node with an error which has been reported, it is
+    /// compatible with every type, so the error is not reported again.
+    Error,
+}
+
+impl Type {
+    /// The type named in the source code, like "int32".
+    pub fn from_name(name: Symbol) -> Option<Type> {
+        Some(match name.as_str() {
+            "int8" => Type::Int8,
+            "int16" => Type::Int16,
+            "int32" => Type::Int32,
+            "int64" => Type::Int64,
+            "int128" => Type::Int128,
+            "uint8" => Type::UInt8,
+            "uint16" => Type::UInt16,
+            "uint32" => Type::UInt32,
+            "uint64" => Type::UInt64,
+            "uint128" => Type::UInt128,
+            "float32" => Type::Float32,
+            "float64" => Type::Float64,
+            "char" => Type::Char,
+            "bool" => Type::Bool,
+            "str" => Type::Str,
+            "bytes" => Type::Bytes,
+            _ => return None,
+        })
+    }
+
+    pub fn of(lit: &Literal) -> Type {
+        match lit {
+            Literal::Byte(_) => Type::Int8,
+            Literal::Int16(_) => Type::Int16,
+            Literal::Int(_) => Type::Int32,
+            Literal::Int64(_) => Type::Int64,
+            Literal::Int128(_) => Type::Int128,
+            Literal::UByte(_) => Type::UInt8,
+            Literal::UInt16(_) => Type::UInt16,
+            Literal::UInt(_) => Type::UInt32,
+            Literal::UInt64(_) => Type::UInt64,
+            Literal::UInt128(_) => Type::UInt128,
+            // the types of the literals without suffix are inferred, these
+            // are their types where nothing is expected.
+            Literal::UnsuffixedInt(_) => Type::Int32,
+            Literal::Float(_) => Type::Float32,
+            Literal::Float64(_) => Type::Float64,
+            Literal::UnsuffixedFloat(_) => Type::Float32,
+            Literal::Char(_) => Type::Char,
+            Literal::Boolean(_) => Type::Bool,
+            Literal::Str(_) => Type::Str,
+            Literal::ByteStr(_) => Type::Bytes,
+            // an invalid literal, the lexer has reported why.
+            Literal::Unexpected(_) => Type::Error,
+        }
+    }
+
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Type::Int8
+                | Type::Int16
+                | Type::Int32
+                | Type::Int64
+                | Type::Int128
+                | Type::UInt8
+                | Type::UInt16
+                | Type::UInt32
+                | Type::UInt64
+                | Type::UInt128
+        )
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Type::Float32 | Type::Float64)
+    }
+
+    pub fn is_numeric(self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    /// Whether the integer type can hold `n`, the literals have no sign.
+    pub fn fits(self, n: u128) -> bool {
+        let max = match self {
+            Type::Int8 => i8::MAX as u128,
+            Type::Int16 => i16::MAX as u128,
+            Type::Int32 => i32::MAX as u128,
+            Type::Int64 => i64::MAX as u128,
+            Type::Int128 => i128::MAX as u128,
+            Type::UInt8 => u8::MAX as u128,
+            Type::UInt16 => u16::MAX as u128,
+            Type::UInt32 => u32::MAX as u128,
+            Type::UInt64 => u64::MAX as u128,
+            _ => u128::MAX,
+        };
+
+        n <= max
+    }
+
+    /// Whether a value of type `other` can be used where `self` is expected.
+    pub fn accepts(self, other: Type) -> bool {
+        self == other || self == Type::Error || other == Type::Error
+    }
+}
+
+/// The name in the source code, like "int32".
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Type::Int8 => "int8",
+            Type::Int16 => "int16",
+            Type::Int32 => "int32",
+            Type::Int64 => "int64",
+            Type::Int128 => "int128",
+            Type::UInt8 => "uint8",
+            Type::UInt16 => "uint16",
+            Type::UInt32 => "uint32",
+            Type::UInt64 => "uint64",
+            Type::UInt128 => "uint128",
+            Type::Float32 => "float32",
+            Type::Float64 => "float64",
+            Type::Char => "char",
+            Type::Bool => "bool",
+            Type::Str => "str",
+            Type::Bytes => "bytes",
+            Type::Unit => "()",
+            Type::Error => "{error}",
+        })
+    }
+}
@@ -1,3 +1,3 @@
-mod firelang_errors;
 pub mod firelang_lexer;
 pub mod firelang_parser;
+pub mod firelang_sema;
@@ -0,0 +1,422 @@
+use crate::compiler::firelang_parser::ast::node::{Module, Statement};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The output format of the documentation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    Html,
+    Markdown,
+}
+
+impl Format {
+    fn ext(&self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Markdown => "md",
+        }
+    }
+}
+
+/// A documented function.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DocFunction {
+    pub name: String,
+    /// "a <- int32"
+    pub params: Vec<String>,
+    pub doc: Option<String>,
+}
+
+impl DocFunction {
+    pub fn signature(&self) -> String {
+        format!("fn {}({})", self.name, self.params.join(", "))
+    }
+
+    /// The first line of the documentation.
+    pub fn summary(&self) -> &str {
+        self.doc
+            .as_deref()
+            .and_then(|x| x.lines().next())
+            .unwrap_or("")
+    }
+}
+
+/// The documented items of a source file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DocModule {
+    pub name: String,
+    pub functions: Vec<DocFunction>,
+}
+
+impl DocModule {
+    /// Collect the top-level functions, they are public to the other modules.
+    pub fn new(name: impl Into<String>, module: &Module) -> DocModule {
+        let functions = module
+            .items
+            .iter()
+            .filter_map(|x| match &x.kind {
+                Statement::FuncDecl {
+                    doc, ident, params, ..
+                } => Some(DocFunction {
+                    name: ident.to_string(),
+                    params: params
+                        .iter()
+                        .map(|(name, bhv, ty)| format!("{name} {bhv} {ty}"))
+                        .collect(),
+                    doc: doc.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        DocModule {
+            name: name.into(),
+            functions,
+        }
+    }
+}
+
+/// A generated file of the site.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Page {
+    pub path: String,
+    pub content: String,
+}
+
+/// Generator Struct
+/// Render the modules into a static site, with a search index in JSON.
+pub struct Generator<'m> {
+    modules: &'m [DocModule],
+    format: Format,
+    /// The modules defining a function, by its name.
+    owners: HashMap<&'m str, Vec<&'m str>>,
+}
+
+impl<'m> Generator<'m> {
+    pub fn new(modules: &'m [DocModule], format: Format) -> Generator<'m> {
+        let mut owners: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for module in modules {
+            for func in &module.functions {
+                owners.entry(&func.name).or_default().push(&module.name);
+            }
+        }
+
+        Generator {
+            modules,
+            format,
+            owners,
+        }
+    }
+
+    pub fn render(&self) -> Vec<Page> {
+        let mut pages = vec![Page {
+            path: format!("index.{}", self.format.ext()),
+            content: self.index(),
+        }];
+
+        // in a directory of their own, a module named "index" would replace
+        // the index otherwise.
+        for module in self.modules {
+            pages.push(Page {
+                path: self.path(&module.name),
+                content: self.module(module),
+            });
+        }
+
+        pages.push(Page {
+            path: "search-index.json".into(),
+            content: self.search_index(),
+        });
+
+        pages
+    }
+
+    /// The page of the module, from the root of the site.
+    fn path(&self, module: &str) -> String {
+        format!("modules/{module}.{}", self.format.ext())
+    }
+
+    /// The link to the function, from the root of the site.
+    fn href(&self, module: &str, func: &str) -> String {
+        format!("{}#fn.{func}", self.path(module))
+    }
+
+    /// Resolve "name" in the current module first, then "module::name" or "name" in the others.
+    fn resolve(&self, target: &str, current: &str) -> Option<String> {
+        let (module, name) = match target.rsplit_once("::") {
+            Some((module, name)) => (Some(module), name),
+            None => (None, target),
+        };
+
+        let owners = self.owners.get(name)?;
+        let owner = match module {
+            Some(module) => owners.iter().find(|&&x| x == module)?,
+            None => owners.iter().find(|&&x| x == current).unwrap_or(&owners[0]),
+        };
+
+        Some(self.href(owner, name))
+    }
+
+    fn index(&self) -> String {
+        match self.format {
+            Format::Html => {
+                let mut list = String::new();
+
+                for module in self.modules {
+                    list += &format!(
+                        "<li><a href=\"{}\">{}</a></li>\n",
+                        escape_html(&self.path(&module.name)),
+                        escape_html(&module.name)
+                    );
+                }
+
+                page_html(
+                    "Documentation",
+                    &format!(
+                        "<h1>Documentation</h1>\n\
+                         <input id=\"search\" placeholder=\"Search functions\">\n\
+                         <ul id=\"results\"></ul>\n\
+                         <h2>Modules</h2>\n<ul>\n{list}</ul>\n<script>{SEARCH_SCRIPT}</script>\n"
+                    ),
+                )
+            }
+
+            Format::Markdown => {
+                let mut out = String::from("# Documentation\n\n## Modules\n\n");
+
+                for module in self.modules {
+                    out += &format!(
+                        "- [{}](<{}>)\n",
+                        escape_markdown(&module.name),
+                        self.path(&module.name)
+                    );
+                }
+
+                out
+            }
+        }
+    }
+
+    fn module(&self, module: &DocModule) -> String {
+        match self.format {
+            Format::Html => {
+                let mut body = format!(
+                    "<p><a href=\"../index.html\">Index</a></p>\n<h1>Module {}</h1>\n",
+                    escape_html(&module.name)
+                );
+
+                for func in &module.functions {
+                    body += &format!(
+                        "<section id=\"fn.{}\">\n<h2><code>{}</code></h2>\n",
+                        escape_html(&func.name),
+                        escape_html(&func.signature())
+                    );
+
+                    for para in func.doc.iter().flat_map(|x| x.split("\n\n")) {
+                        body += &format!("<p>{}</p>\n", self.inline_html(para, &module.name));
+                    }
+
+                    body += "</section>\n";
+                }
+
+                page_html(&module.name, &body)
+            }
+
+            Format::Markdown => {
+                let mut out = format!(
+                    "[Index](../index.md)\n\n# Module {}\n",
+                    escape_markdown(&module.name)
+                );
+
+                for func in &module.functions {
+                    out += &format!(
+                        "\n<a id=\"fn.{}\"></a>\n## `{}`\n",
+                        escape_html(&func.name),
+                        func.signature()
+                    );
+
+                    if let Some(doc) = &func.doc {
+                        out += &format!("\n{}\n", self.inline_markdown(doc, &module.name));
+                    }
+                }
+
+                out
+            }
+        }
+    }
+
+    /// Render a paragraph, with code spans and the links to the other items.
+    fn inline_html(&self, text: &str, current: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+
+        while let Some(i) = rest.find(['`', '[']) {
+            out += &escape_html(&rest[..i]);
+            rest = &rest[i..];
+
+            if let Some((target, len)) = link(rest) {
+                match self.resolve(target, current) {
+                    Some(href) => {
+                        out += &format!(
+                            "<a href=\"../{}\"><code>{}</code></a>",
+                            escape_html(&href),
+                            escape_html(target)
+                        )
+                    }
+                    None => out += &escape_html(&rest[..len]),
+                }
+
+                rest = &rest[len..];
+            } else if let Some(end) = rest[1..].find('`').filter(|_| rest.starts_with('`')) {
+                out += &format!("<code>{}</code>", escape_html(&rest[1..end + 1]));
+                rest = &rest[end + 2..];
+            } else {
+                out += &escape_html(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+
+        out + &escape_html(rest)
+    }
+
+    /// Render a paragraph, with the code spans kept, the links to the other
+    /// items pointed at their pages and the rest escaped.
+    fn inline_markdown(&self, text: &str, current: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+
+        while let Some(i) = rest.find(['`', '[']) {
+            out += &escape_markdown(&rest[..i]);
+            rest = &rest[i..];
+
+            if let Some((target, len)) = link(rest) {
+                match self.resolve(target, current) {
+                    Some(href) => out += &format!("[`{target}`](<../{href}>)"),
+                    None => out += &escape_markdown(&rest[..len]),
+                }
+
+                rest = &rest[len..];
+            } else if let Some(end) = rest[1..].find('`').filter(|_| rest.starts_with('`')) {
+                out += &rest[..end + 2];
+                rest = &rest[end + 2..];
+            } else {
+                out += &escape_markdown(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+
+        out + &escape_markdown(rest)
+    }
+
+    /// [{"name": .., "module": .., "path": .., "signature": .., "summary": ..}, ..]
+    fn search_index(&self) -> String {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            name: &'a str,
+            module: &'a str,
+            path: String,
+            signature: String,
+            summary: &'a str,
+        }
+
+        let entries: Vec<String> = self
+            .modules
+            .iter()
+            .flat_map(|module| {
+                module.functions.iter().map(|func| {
+                    serde_json::to_string(&Entry {
+                        name: &func.name,
+                        module: &module.name,
+                        path: self.href(&module.name, &func.name),
+                        signature: func.signature(),
+                        summary: func.summary(),
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+
+        format!("[{}]\n", entries.join(",\n"))
+    }
+}
+
+/// "[`name`]" or "[name]", but not the "[text](url)" links. Returns the target and the length.
+fn link(text: &str) -> Option<(&str, usize)> {
+    let end = text.strip_prefix('[')?.find(']')? + 1;
+
+    if text[end + 1..].starts_with('(') {
+        return None;
+    }
+
+    let target = text[1..end].trim_matches('`');
+
+    if target.is_empty() || target.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some((target, end + 1))
+}
+
+fn page_html(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Escape the characters which start an inline markup anywhere, and the
+/// ones which start a heading or a bullet list at the beginning of a line.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut line_start = true;
+
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '|' | '~' => out.push('\\'),
+            '#' | '-' | '+' | '=' if line_start => out.push('\\'),
+            _ => (),
+        }
+
+        out.push(c);
+        line_start = c == '\n' || (line_start && c == ' ');
+    }
+
+    out
+}
+
+const SEARCH_SCRIPT: &str = r#"
+fetch("search-index.json").then(r => r.json()).then(index => {
+    const input = document.getElementById("search");
+    const results = document.getElementById("results");
+    input.addEventListener("input", () => {
+        const q = input.value.toLowerCase();
+        results.innerHTML = "";
+        if (!q) return;
+        for (const x of index.filter(x => x.name.toLowerCase().includes(q))) {
+            const li = document.createElement("li");
+            const a = document.createElement("a");
+            a.href = x.path;
+            a.textContent = x.module + "::" + x.name;
+            li.append(a, " " + x.summary);
+            results.append(li);
+        }
+    });
+});
+"#;
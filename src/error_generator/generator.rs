@@ -1 +1,126 @@
+use std::fmt::{Display, Formatter};
 
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Error => f.write_str("error"),
+            Level::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A message about the source code, located at a line and column.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn note(&mut self, note: impl Into<String>) -> &mut Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}\n  --> line {}, col {}",
+            self.level, self.message, self.line, self.column
+        )?;
+
+        for note in &self.notes {
+            write!(f, "\n   = note: {note}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The sink collecting the diagnostics of a compiling pass.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics { items: Vec::new() }
+    }
+
+    pub fn push(
+        &mut self,
+        level: Level,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        self.items.push(Diagnostic {
+            level,
+            message: message.into(),
+            line,
+            column,
+            notes: Vec::new(),
+        });
+
+        self.items.last_mut().unwrap()
+    }
+
+    pub fn error(
+        &mut self,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        self.push(Level::Error, line, column, message)
+    }
+
+    pub fn warning(
+        &mut self,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> &mut Diagnostic {
+        self.push(Level::Warning, line, column, message)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(|x| x.level == Level::Error)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.items.extend(other.items);
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
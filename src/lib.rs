@@ -2,4 +2,5 @@ extern crate core;
 
 pub mod vm;
 pub mod compiler;
+pub mod doc_generator;
 pub mod error_generator;
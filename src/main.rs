@@ -1,29 +1,283 @@
-use firelang::compiler::firelang_lexer::lexer::*;
-use firelang::compiler::firelang_parser::ast::node::Statement;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use firelang::compiler::firelang_lexer::lexer::{Lexer, TokenSource};
+use firelang::compiler::firelang_lexer::stream::StreamLexer;
+use firelang::compiler::firelang_parser::ast::json;
+use firelang::compiler::firelang_parser::ast::node::Module;
+use firelang::compiler::firelang_parser::ast::printer::Printer;
+use firelang::compiler::firelang_parser::ast::sexp::Sexp;
 use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::compiler::firelang_sema::resolve::Resolver;
+use firelang::compiler::firelang_sema::typeck::TypeChecker;
+use firelang::doc_generator::generator::{DocModule, Format, Generator};
+use std::fs::File;
 use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
 
-fn main() {
-    let mut buffer = String::new();
-    std::fs::File::open("./test/parser_test.test")
-        .unwrap()
-        .read_to_string(&mut buffer)
-        .expect("ERROR");
-
-    let lexer = Lexer::new(buffer.as_str());
-    let mut parser = Parser::new(lexer);
-
-    loop {
-        let expr = parser.parse();
-        if let Ok(expr) = expr {
-            if expr != Statement::Eof {
-                println!("{expr:#?}");
-            } else {
-                break;
+fn main() -> ExitCode {
+    let matches = Command::new("fire")
+        .about("The Fire programming language")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("check")
+                .about("Check a source file for errors without running it")
+                .arg(
+                    Arg::new("input")
+                        .required(true)
+                        .help("The source file, or `-` to read from stdin"),
+                ),
+        )
+        .subcommand(
+            Command::new("parse")
+                .about("Print the syntax tree of a source file")
+                .arg(
+                    Arg::new("input")
+                        .required(true)
+                        .help("The source file, or `-` to read from stdin"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["json", "sexp"])
+                        .default_value("json")
+                        .help("The format of the tree"),
+                ),
+        )
+        .subcommand(
+            Command::new("doc")
+                .about("Generate the documentation of the source files")
+                .arg(
+                    Arg::new("inputs")
+                        .required(true)
+                        .num_args(1..)
+                        .help("The source files, each of them is a module"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .short('o')
+                        .long("out")
+                        .default_value("doc")
+                        .help("The directory to write the site to"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["html", "markdown"])
+                        .default_value("html")
+                        .help("The format of the pages"),
+                ),
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Format the source files in place")
+                .arg(
+                    Arg::new("inputs")
+                        .required(true)
+                        .num_args(1..)
+                        .help("The source files, or `-` to format stdin to stdout"),
+                )
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .action(ArgAction::SetTrue)
+                        .help("Only check that the files are formatted, fail if any is not"),
+                ),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        Some(("check", m)) => check(m),
+        Some(("parse", m)) => parse(m),
+        Some(("doc", m)) => doc(m),
+        Some(("fmt", m)) => fmt(m),
+        _ => unreachable!(),
+    }
+}
+
+fn check(m: &ArgMatches) -> ExitCode {
+    let input = m.get_one::<String>("input").unwrap();
+
+    // the source is lexed while being read, so large inputs are never loaded at once.
+    let ok = if input == "-" {
+        check_source(&mut Parser::new(StreamLexer::new(std::io::stdin().lock())))
+    } else {
+        match File::open(input) {
+            Ok(file) => check_source(&mut Parser::new(StreamLexer::new(file))),
+            Err(e) => {
+                eprintln!("error: cannot open `{input}`: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn parse(m: &ArgMatches) -> ExitCode {
+    let input = m.get_one::<String>("input").unwrap();
+
+    let module = if input == "-" {
+        parse_source(&mut Parser::new(StreamLexer::new(std::io::stdin().lock())))
+    } else {
+        match File::open(input) {
+            Ok(file) => parse_source(&mut Parser::new(StreamLexer::new(file))),
+            Err(e) => {
+                eprintln!("error: cannot open `{input}`: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    let Some(module) = module else {
+        return ExitCode::FAILURE;
+    };
+
+    match m.get_one::<String>("format").unwrap().as_str() {
+        "sexp" => println!("{}", Sexp::from(&module)),
+        _ => println!("{}", json::to_string(&module)),
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn doc(m: &ArgMatches) -> ExitCode {
+    let out = Path::new(m.get_one::<String>("out").unwrap());
+    let format = match m.get_one::<String>("format").unwrap().as_str() {
+        "markdown" => Format::Markdown,
+        _ => Format::Html,
+    };
+
+    let mut modules = Vec::new();
+
+    for input in m.get_many::<String>("inputs").unwrap() {
+        let file = match File::open(input) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("error: cannot open `{input}`: {e}");
+                return ExitCode::FAILURE;
             }
+        };
+
+        let Some(module) = parse_source(&mut Parser::new(StreamLexer::new(file))) else {
+            return ExitCode::FAILURE;
+        };
+
+        let name = Path::new(input).file_stem().unwrap().to_string_lossy();
+        modules.push(DocModule::new(name, &module));
+    }
+
+    let pages = Generator::new(&modules, format).render();
+
+    for page in pages {
+        let path = out.join(&page.path);
+        let dir = path.parent().unwrap();
+
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("error: cannot create `{}`: {e}", dir.display());
+            return ExitCode::FAILURE;
+        }
+
+        if let Err(e) = std::fs::write(&path, page.content) {
+            eprintln!("error: cannot write `{}`: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn fmt(m: &ArgMatches) -> ExitCode {
+    let check = m.get_flag("check");
+    let mut unformatted = false;
+
+    for input in m.get_many::<String>("inputs").unwrap() {
+        let mut src = String::new();
+
+        let read = if input == "-" {
+            std::io::stdin().read_to_string(&mut src)
         } else {
-            println!("{}", expr.unwrap_err());
-            break;
+            File::open(input).and_then(|mut x| x.read_to_string(&mut src))
+        };
+
+        if let Err(e) = read {
+            eprintln!("error: cannot read `{input}`: {e}");
+            return ExitCode::FAILURE;
+        }
+
+        // the comments and the literals are copied from the source, it is read at once.
+        let mut parser = Parser::new(Lexer::new(&src));
+        let Some(module) = parse_source(&mut parser) else {
+            return ExitCode::FAILURE;
+        };
+
+        let formatted = Printer::new(&src, parser.layout()).print_module(&module);
+
+        if check {
+            if formatted != src {
+                println!("`{input}` is not formatted");
+                unformatted = true;
+            }
+        } else if input == "-" {
+            print!("{formatted}");
+        } else if formatted != src {
+            if let Err(e) = std::fs::write(input, formatted) {
+                eprintln!("error: cannot write `{input}`: {e}");
+                return ExitCode::FAILURE;
+            }
         }
     }
+
+    if unformatted {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Parse the whole source, and report all the errors.
+fn parse_source<'a>(parser: &mut Parser<'a, impl TokenSource<'a>>) -> Option<Module> {
+    let module = parser.parse_module();
+
+    let mut diagnostics: Vec<_> = parser
+        .diagnostics()
+        .iter()
+        .chain(parser.errors().iter())
+        .chain(parser.lints().iter())
+        .collect();
+    diagnostics.sort_by_key(|x| (x.line, x.column));
+
+    for diagnostic in diagnostics {
+        eprintln!("{diagnostic}");
+    }
+
+    let failed = parser.diagnostics().has_errors() || parser.errors().has_errors();
+    (!failed).then_some(module)
+}
+
+/// Parse the whole source and resolve the names in it, and report all the errors.
+fn check_source<'a>(parser: &mut Parser<'a, impl TokenSource<'a>>) -> bool {
+    let Some(module) = parse_source(parser) else {
+        return false;
+    };
+
+    let resolutions = Resolver::new(parser.positions()).resolve_module(&module);
+    let types = TypeChecker::new(parser.positions(), &resolutions).check_module(&module);
+
+    let mut errors: Vec<_> = resolutions
+        .errors
+        .iter()
+        .chain(types.errors.iter())
+        .collect();
+    errors.sort_by_key(|x| (x.line, x.column));
+
+    for x in &errors {
+        eprintln!("{x}");
+    }
+
+    !resolutions.errors.has_errors() && !types.errors.has_errors()
 }
@@ -1,20 +1,47 @@
+use crate::vm::context::ByteCode;
 use crate::compiler::firelang_parser::ast::node::*;
+use crate::compiler::firelang_parser::ast::token::{BinaryOp, Literal};
 
 
 pub trait Generator {
-    fn gen(&mut self) -> ByteCode;
+    fn gen(&mut self, exprs: &mut Exprs) -> ByteCode;
 }
 
 impl Generator for Expression {
-    fn gen(&mut self) -> ByteCode {
-        match &self {
-            Expression::Binary { lhs, op, rhs} => {
+    fn gen(&mut self, exprs: &mut Exprs) -> ByteCode {
+        match self {
+            Expression::Binary { .. } => {
 
             }
 
+            Expression::Interpolated { parts } => {
+                return lower_interpolated(exprs, std::mem::take(parts)).gen(exprs);
+            }
+
             _ => (),
         }
 
         unimplemented!()
     }
+}
+
+/// Lower `"a + b = {a + b}!"` to `"a + b = " + to_string(a + b) + "!"`.
+/// The new nodes have the dummy id and are put in `exprs`, the embedded
+/// expressions keep theirs.
+pub fn lower_interpolated(exprs: &mut Exprs, parts: Vec<InterpolatedPart>) -> Expression {
+    parts
+        .into_iter()
+        .map(|x| match x {
+            InterpolatedPart::Str(x) => Expression::Literal(Literal::Str(x)),
+            InterpolatedPart::Expr(x) => Expression::FuncCall {
+                ident: "to_string".into(),
+                args: vec![x],
+            },
+        })
+        .reduce(|lhs, rhs| Expression::Binary {
+            lhs: exprs.alloc(lhs.into()),
+            op: BinaryOp::Add,
+            rhs: exprs.alloc(rhs.into()),
+        })
+        .unwrap_or(Expression::Literal(Literal::Str("".into())))
 }
\ No newline at end of file
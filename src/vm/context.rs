@@ -1,11 +1,11 @@
 use std::collections::HashMap;
-enum OpType {
+pub enum OpType {
     Add,
     Mul,
 }
 
 pub struct ByteCode {
-    opcode: OpType
+    pub opcode: OpType
 }
 
 impl ByteCode {
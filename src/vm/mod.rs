@@ -1,2 +1,2 @@
 pub mod codegen;
-mod context;
+pub mod context;
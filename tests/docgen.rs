@@ -0,0 +1,97 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::doc_generator::generator::{DocModule, Format, Generator, Page};
+
+fn module(name: &str, src: &str) -> DocModule {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    DocModule::new(name, &module)
+}
+
+fn modules() -> Vec<DocModule> {
+    vec![
+        module(
+            "index",
+            "/// Add two numbers, see [`math::sub`].\n///\n/// The sum is `a + b`.\nfn add(a <- int32, b <- int32) { return a + b; }\n",
+        ),
+        module(
+            "math",
+            "/// Subtract, like [add] but *not* <b>bold</b>.\n/// # not a heading\nfn sub(a <- int32) { return a; }\nlet x = 1;\n",
+        ),
+    ]
+}
+
+fn page<'p>(pages: &'p [Page], path: &str) -> &'p str {
+    match pages.iter().find(|x| x.path == path) {
+        Some(x) => &x.content,
+        None => panic!(
+            "no {path} in {:?}",
+            pages.iter().map(|x| &x.path).collect::<Vec<_>>()
+        ),
+    }
+}
+
+#[test]
+fn renders_a_page_per_module() {
+    let modules = modules();
+    let pages = Generator::new(&modules, Format::Html).render();
+
+    let paths: Vec<_> = pages.iter().map(|x| x.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "index.html",
+            "modules/index.html",
+            "modules/math.html",
+            "search-index.json"
+        ]
+    );
+
+    // a module named "index" doesn't replace the index.
+    let index = page(&pages, "index.html");
+    assert!(index.contains("<a href=\"modules/index.html\">index</a>"));
+    assert!(index.contains("<a href=\"modules/math.html\">math</a>"));
+
+    let math = page(&pages, "modules/math.html");
+    assert!(math.contains("<a href=\"../index.html\">Index</a>"));
+    assert!(math.contains("<section id=\"fn.sub\">"));
+    assert!(math.contains("&lt;b&gt;bold&lt;/b&gt;"));
+    assert!(math.contains("<a href=\"../modules/index.html#fn.add\"><code>add</code></a>"));
+    assert!(!math.contains("fn.x"), "only the functions are documented");
+
+    let add = page(&pages, "modules/index.html");
+    assert!(add.contains("<code>fn add(a &lt;- int32, b &lt;- int32)</code>"));
+    assert!(add.contains("<a href=\"../modules/math.html#fn.sub\"><code>math::sub</code></a>"));
+    assert!(add.contains("<p>The sum is <code>a + b</code>.</p>"));
+
+    assert_eq!(
+        page(&pages, "search-index.json"),
+        "[{\"name\":\"add\",\"module\":\"index\",\"path\":\"modules/index.html#fn.add\",\
+         \"signature\":\"fn add(a <- int32, b <- int32)\",\"summary\":\"Add two numbers, see [`math::sub`].\"},\n\
+         {\"name\":\"sub\",\"module\":\"math\",\"path\":\"modules/math.html#fn.sub\",\
+         \"signature\":\"fn sub(a <- int32)\",\"summary\":\"Subtract, like [add] but *not* <b>bold</b>.\"}]\n"
+    );
+}
+
+#[test]
+fn escapes_the_markdown() {
+    let mut modules = modules();
+    modules[1].name = "my_math".into();
+    modules[0].functions[0].doc = Some("See [`my_math::sub`], not `a_b`.".into());
+    let pages = Generator::new(&modules, Format::Markdown).render();
+
+    assert!(page(&pages, "index.md").contains("- [my\\_math](<modules/my_math.md>)"));
+
+    let math = page(&pages, "modules/my_math.md");
+    assert!(math.starts_with("[Index](../index.md)\n\n# Module my\\_math\n"));
+    assert!(math.contains(
+        "Subtract, like [`add`](<../modules/index.md#fn.add>) but \\*not\\* \\<b\\>bold\\</b\\>.\n\\# not a heading"
+    ));
+
+    // the code spans are kept as they are.
+    let add = page(&pages, "modules/index.md");
+    assert!(add.contains("See [`my_math::sub`](<../modules/my_math.md#fn.sub>), not `a_b`."));
+    assert!(page(&pages, "search-index.json").contains("\"path\":\"modules/my_math.md#fn.sub\""));
+}
@@ -0,0 +1,251 @@
+use firelang::compiler::firelang_lexer::lexer::{Lexer, Span, TokenKind};
+use firelang::compiler::firelang_parser::ast::node::{NodeId, Statement};
+use firelang::compiler::firelang_parser::document::Document;
+use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::error_generator::generator::Diagnostic;
+
+const SOURCE: &str = r#"let a = 1;
+let b <- a + 2 * (3 - 4);
+/* a /* nested */ comment */
+let s = "sum: {a + b}";
+// line comment
+let c = 'x';
+return a;
+"#;
+
+/// The edited document must be the same as the one built from scratch.
+fn assert_full_reparse(doc: &Document) {
+    let full = Document::new(doc.src());
+
+    assert_eq!(doc.tokens(), full.tokens(), "source: {:?}", doc.src());
+    assert_eq!(
+        doc.diagnostics().collect::<Vec<_>>(),
+        full.diagnostics().collect::<Vec<_>>()
+    );
+    assert_eq!(doc.items().len(), full.items().len());
+
+    assert_eq!(doc.lints(), full.lints());
+
+    for (x, y) in doc.items().iter().zip(full.items()) {
+        assert_eq!(x.span, y.span);
+        assert!(x.same_stmt(y), "{:?} != {:?}", x.stmt, y.stmt);
+    }
+
+    // the ids differ, the positions of the nodes at the same place don't.
+    let positions = |doc: &Document| {
+        let mut x: Vec<_> = doc.positions().into_values().collect();
+        x.sort();
+        x
+    };
+    assert_eq!(positions(doc), positions(&full));
+}
+
+fn edit(doc: &mut Document, start: usize, end: usize, text: &str) {
+    doc.apply_edit(Span { start, end }, text);
+    assert_full_reparse(doc);
+}
+
+#[test]
+fn new_document_matches_lexer() {
+    let doc = Document::new(SOURCE);
+    let mut lexer = Lexer::new(SOURCE);
+
+    for x in doc.tokens() {
+        assert_eq!(*x, lexer.next_token());
+    }
+
+    assert_eq!(doc.tokens().last().unwrap().kind, TokenKind::Eof);
+    assert_eq!(doc.items().len(), 5);
+}
+
+#[test]
+fn edits_match_full_reparse() {
+    let mut doc = Document::new(SOURCE);
+    let at = |doc: &Document, s: &str| doc.src().find(s).unwrap();
+
+    // change a value.
+    let x = at(&doc, "1;");
+    edit(&mut doc, x, x + 1, "42");
+
+    // insert a statement.
+    let x = at(&doc, "let c");
+    edit(&mut doc, x, x, "let d = 0.5e3;\n");
+
+    // open a block comment which swallows the rest, then close it.
+    let x = at(&doc, "let s");
+    edit(&mut doc, x, x, "/* ");
+    edit(&mut doc, x, x + 3, "");
+
+    // break a string, then fix it.
+    let x = at(&doc, "\";");
+    edit(&mut doc, x, x + 1, "");
+    edit(&mut doc, x, x, "\"");
+
+    // join and split lines.
+    let x = at(&doc, "\n// line");
+    edit(&mut doc, x, x + 1, " ");
+    edit(&mut doc, x, x + 1, "\n\n");
+
+    // an unknown character and a syntax error.
+    let x = at(&doc, "return");
+    edit(&mut doc, x, x, "@ ");
+    edit(&mut doc, x, x + 2, "let = ;\n");
+
+    // delete everything, then paste it back.
+    let len = doc.src().len();
+    edit(&mut doc, 0, len, "");
+    edit(&mut doc, 0, 0, SOURCE);
+}
+
+#[test]
+fn random_edits_match_full_reparse() {
+    const PIECES: &[&str] = &[
+        "",
+        "x",
+        "1",
+        ".5e",
+        "0x",
+        " ",
+        "\n",
+        "\"",
+        "'",
+        "{",
+        "}",
+        ";",
+        "/*",
+        "*/",
+        "//",
+        "r\"(",
+        ")\"",
+        "中文",
+        "let a = 1;\n",
+        "return a + b;\n",
+    ];
+
+    let src = [SOURCE, include_str!("../test/lexer_test.fire")].concat();
+    let mut doc = Document::new(&src);
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut rand = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n as u64) as usize
+    };
+
+    for _ in 0..300 {
+        let len = doc.src().len();
+        let mut start = rand(len + 1);
+        let mut end = (start + rand(8)).min(len);
+
+        while !doc.src().is_char_boundary(start) {
+            start -= 1;
+        }
+        while !doc.src().is_char_boundary(end) {
+            end += 1;
+        }
+
+        let text = PIECES[rand(PIECES.len())];
+        edit(&mut doc, start, end, text);
+    }
+}
+
+#[test]
+fn unchanged_parts_are_reused() {
+    let src: String = (0..100).map(|i| format!("let v{i} = {i};\n")).collect();
+    let mut doc = Document::new(&src);
+    assert_eq!(doc.items().len(), 100);
+
+    let x = doc.src().find("= 50;").unwrap() + 2;
+    let changed = doc.apply_edit(
+        Span {
+            start: x,
+            end: x + 2,
+        },
+        "5 + 5",
+    );
+    assert_full_reparse(&doc);
+
+    assert!(changed.tokens.len() < 30, "{changed:?}");
+    assert!(changed.items.len() < 5, "{changed:?}");
+}
+
+#[test]
+fn reused_items_keep_their_ids() {
+    let src: String = (0..100).map(|i| format!("let v{i} = {i};\n")).collect();
+    let mut doc = Document::new(&src);
+    let ids = |doc: &Document| -> Vec<NodeId> { doc.items().iter().map(|x| x.stmt.id).collect() };
+    let before = ids(&doc);
+
+    let x = doc.src().find("= 50;").unwrap() + 2;
+    let changed = doc.apply_edit(
+        Span {
+            start: x,
+            end: x + 2,
+        },
+        "5 + 5",
+    );
+    let after = ids(&doc);
+
+    for (i, id) in after.iter().enumerate() {
+        match changed.items.contains(&i) {
+            true => assert!(!before.contains(id), "{i}: {id:?}"),
+            false => assert_eq!(*id, before[i], "{i}"),
+        }
+    }
+}
+
+/// The messages of the diagnostics, with their positions.
+fn messages<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Vec<String> {
+    diagnostics
+        .into_iter()
+        .map(|x| format!("{}:{}: {}", x.line, x.column, x.message))
+        .collect()
+}
+
+#[test]
+fn reports_what_the_parser_reports() {
+    let src = "let = 1;\nlet s = \"{a $}\";\nlet а = 1;\nlet a = 2;\nlet = 2;\n";
+    let doc = Document::new(src);
+
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+
+    // the parsing goes on after the errors.
+    assert_eq!(doc.items().len(), module.items.len());
+    assert!(matches!(doc.items()[0].stmt.kind, Statement::Error));
+    assert!(matches!(
+        doc.items()[3].stmt.kind,
+        Statement::VariableDecl { .. }
+    ));
+
+    let mut expected = messages(parser.diagnostics().iter().chain(parser.errors().iter()));
+    let mut found = messages(doc.diagnostics());
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
+    assert!(found.iter().any(|x| x.contains("unknown character `$`")));
+
+    assert_eq!(doc.lints(), *parser.lints());
+    assert_eq!(doc.lints().len(), 1);
+    assert_eq!(doc.positions(), *parser.positions());
+}
+
+#[test]
+fn edits_move_the_diagnostics_and_the_lints() {
+    let src = "let а = 1;\nlet b = 0;\nlet = 2;\nlet a = \"{1 $}\";\n";
+    let mut doc = Document::new(src);
+    let lints = messages(doc.lints().iter());
+    assert_eq!(lints.len(), 1, "{lints:?}");
+
+    // the lines after the edit are reused, but their diagnostics move.
+    let x = doc.src().find("let b").unwrap();
+    edit(&mut doc, x, x, "\n\n");
+    assert_eq!(
+        messages(doc.lints().iter()),
+        ["6:5: identifier `a` is confusable with `а`"]
+    );
+
+    // a new declaration changes the lints of the reused items too.
+    edit(&mut doc, 0, 0, "let a = 0;\n");
+    edit(&mut doc, 0, 0, "fn f() { let а = 1; }\n");
+}
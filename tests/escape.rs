@@ -0,0 +1,115 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_lexer::unescape::{escape, escape_char};
+use firelang::compiler::firelang_parser::ast::node::Expression;
+use firelang::compiler::firelang_parser::ast::token::Literal;
+use firelang::compiler::firelang_parser::parser::Parser;
+
+/// Parse the literal, it must be the whole source without any diagnostic.
+fn lex(src: &str) -> Literal {
+    let mut parser = Parser::new(Lexer::new(src));
+    let expr = parser.parse_primary();
+
+    assert!(
+        parser.diagnostics().is_empty(),
+        "{src}: {:?}",
+        parser.diagnostics()
+    );
+    assert!(!parser.has_content(), "{src}");
+
+    match expr.map(|x| parser.exprs()[x].kind.clone()) {
+        Ok(Expression::Literal(x)) => x,
+        x => panic!("{src}: {x:?}"),
+    }
+}
+
+fn assert_round_trip(s: &str) {
+    let src = escape(s);
+    assert_eq!(lex(&src), Literal::Str(s.into()), "{src}");
+}
+
+#[test]
+fn escapes_strings() {
+    assert_eq!(escape("hello"), r#""hello""#);
+    assert_eq!(escape("a\nb\tc"), r#""a\nb\tc""#);
+    assert_eq!(escape("{a}"), r#""{{a}}""#);
+    assert_eq!(escape("\0\u{7f}\u{202e}"), r#""\u{0}\u{7f}\u{202e}""#);
+    assert_eq!(escape("中文"), r#""中文""#);
+
+    // a raw string when it is shorter.
+    assert_eq!(escape(r"C:\dir\file"), r#""C:\\dir\\file""#);
+    assert_eq!(escape(r"\\host\dir\file"), r#"r"(\\host\dir\file)""#);
+    assert_eq!(escape(r#"say "hi""#), r#"r"(say "hi")""#);
+    assert_eq!(escape(r#"f(")") {}"#), r##"r"#(f(")") {})#""##);
+
+    // but cannot hold a '\r'.
+    assert_eq!(escape("\"\r\""), r#""\x22\r\x22""#);
+}
+
+#[test]
+fn escapes_chars() {
+    assert_eq!(escape_char('a'), "'a'");
+    assert_eq!(escape_char('\''), r"'\''");
+    assert_eq!(escape_char('"'), "'\"'");
+    assert_eq!(escape_char('\\'), r"'\\'");
+    assert_eq!(escape_char('\0'), r"'\u{0}'");
+
+    for c in [
+        'a', '\'', '"', '\\', '\n', '{', '\0', '\u{301}', '\u{feff}', '中', '😀',
+    ] {
+        assert_eq!(lex(&escape_char(c)), Literal::Char(c), "{c:?}");
+    }
+}
+
+#[test]
+fn round_trips_fixed_strings() {
+    for s in [
+        "",
+        "\"",
+        "\"\"",
+        "\"\"\"",
+        "\\",
+        ")\"",
+        ")#\"",
+        "a)",
+        "{{}}",
+        "\r\n",
+        "\u{301}e",
+        "\u{feff}",
+        "x\u{10ffff}",
+    ] {
+        assert_round_trip(s);
+    }
+}
+
+#[test]
+fn round_trips_random_strings() {
+    // the special chars of the literals are more likely than the others.
+    const SPECIAL: &[char] = &[
+        '"', '\'', '\\', '{', '}', '(', ')', '#', 'r', 'x', 'u', ' ', '\n', '\r', '\t', '\0',
+    ];
+
+    let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+    let mut rand = |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % n
+    };
+
+    for _ in 0..10000 {
+        let len = rand(16);
+        let s: String = (0..len)
+            .map(|_| match rand(4) {
+                0 => SPECIAL[rand(SPECIAL.len() as u64) as usize],
+                1 => char::from(rand(0x80) as u8),
+                _ => loop {
+                    if let Some(c) = char::from_u32(rand(0x11_0000) as u32) {
+                        break c;
+                    }
+                },
+            })
+            .collect();
+
+        assert_round_trip(&s);
+    }
+}
@@ -0,0 +1,118 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::node::{
+    Expression, Exprs, InterpolatedPart, Statement,
+};
+use firelang::compiler::firelang_parser::ast::token::{BinaryOp, Literal};
+use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::vm::codegen::lower_interpolated;
+
+/// The parts of the string literal in `let s = <literal>;`, and the
+/// expressions of the module.
+fn parts(src: &str) -> (Exprs, Vec<InterpolatedPart>) {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(
+        parser.diagnostics().is_empty(),
+        "{:?}",
+        parser.diagnostics()
+    );
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    let mut exprs = module.exprs;
+
+    match module.items.into_iter().next().map(|x| x.kind) {
+        Some(Statement::VariableDecl { value, .. }) => match &mut exprs[value].kind {
+            Expression::Interpolated { parts } => {
+                let parts = std::mem::take(parts);
+                (exprs, parts)
+            }
+            x => panic!("not interpolated: {x:?}"),
+        },
+        x => panic!("{x:?}"),
+    }
+}
+
+/// The diagnostics of all kinds of parsing the source.
+fn diagnostics(src: &str) -> Vec<String> {
+    let mut parser = Parser::new(Lexer::new(src));
+    parser.parse_module();
+
+    parser
+        .diagnostics()
+        .iter()
+        .chain(parser.errors().iter())
+        .chain(parser.lints().iter())
+        .map(|x| format!("{}:{}: {}", x.line, x.column, x.message))
+        .collect()
+}
+
+#[test]
+fn parses_the_embedded_expressions() {
+    let (exprs, parts) = parts(r#"let s = "a + b = {a + b}, {{c}} = {f(c)}";"#);
+
+    let [InterpolatedPart::Str(a), InterpolatedPart::Expr(sum), InterpolatedPart::Str(b), InterpolatedPart::Expr(call)] =
+        &parts[..]
+    else {
+        panic!("{parts:?}");
+    };
+
+    assert_eq!(a, "a + b = ");
+    assert_eq!(b, ", {c} = ");
+    assert!(matches!(
+        &exprs[*sum].kind,
+        Expression::Binary {
+            op: BinaryOp::Add,
+            ..
+        }
+    ));
+    assert!(matches!(&exprs[*call].kind, Expression::FuncCall { args, .. } if args.len() == 1));
+}
+
+#[test]
+fn reports_the_errors_of_the_embedded_expressions() {
+    // the stray character is reported where it is in the file.
+    assert_eq!(
+        diagnostics("let s = \"{a $}\";"),
+        ["1:13: unknown character `$` (U+0024)"]
+    );
+
+    let errors = diagnostics("let s = \"x\";\nlet t = \"{a +}\";");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        errors[0].contains("in the embedded expression"),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn lowers_to_a_concatenation() {
+    let (mut exprs, parts) = parts(r#"let s = "n = {n}!";"#);
+    let lowered = lower_interpolated(&mut exprs, parts);
+
+    let Expression::Binary { lhs, op, rhs } = lowered else {
+        panic!("{lowered:?}");
+    };
+    assert_eq!(op, BinaryOp::Add);
+    assert_eq!(
+        exprs[rhs].kind,
+        Expression::Literal(Literal::Str("!".into()))
+    );
+
+    let Expression::Binary { lhs, rhs, .. } = &exprs[lhs].kind else {
+        panic!("{lhs:?}");
+    };
+    assert_eq!(
+        exprs[*lhs].kind,
+        Expression::Literal(Literal::Str("n = ".into()))
+    );
+    assert!(matches!(
+        &exprs[*rhs].kind,
+        Expression::FuncCall { ident, args } if ident.as_str() == "to_string" && args.len() == 1
+    ));
+
+    // without parts it is the empty string.
+    assert_eq!(
+        lower_interpolated(&mut exprs, Vec::new()),
+        Expression::Literal(Literal::Str("".into()))
+    );
+}
@@ -0,0 +1,311 @@
+use firelang::compiler::firelang_lexer::lexer::{
+    Lexer, LiteralKind, RawStrError, StrPart, Token, TokenKind,
+};
+use firelang::compiler::firelang_parser::ast::node::{Expression, Statement};
+use firelang::compiler::firelang_parser::ast::token::Literal;
+use firelang::compiler::firelang_parser::parser::Parser;
+
+/// The diagnostics of lexing the whole source, with their notes.
+fn diagnostics(src: &str) -> Vec<String> {
+    let mut lexer = Lexer::new(src);
+    while lexer.next_token().kind != TokenKind::Eof {}
+
+    lexer
+        .diagnostics
+        .iter()
+        .map(|x| {
+            let mut s = format!("{}:{}: {}", x.line, x.column, x.message);
+            for note in &x.notes {
+                s += &format!(" ({note})");
+            }
+            s
+        })
+        .collect()
+}
+
+#[test]
+fn reports_the_char_literals_of_more_than_one_codepoint() {
+    assert_eq!(
+        diagnostics("let c = 'ab';\nlet d = '';"),
+        [
+            "1:9: character literal may only contain one codepoint \
+             (if you meant to write a string literal, use double quotes: \"ab\")",
+            "2:9: empty character literal",
+        ]
+    );
+
+    // a base char and a combining mark are two codepoints too.
+    assert_eq!(diagnostics("'e\u{301}'").len(), 1);
+    assert!(diagnostics(r"'\u{1F600}' '中' '\''").is_empty());
+}
+
+/// The tokens of the source but the whitespace and the end of file.
+fn tokens(src: &str) -> Vec<Token<'_>> {
+    let mut lexer = Lexer::new(src);
+    let mut tokens = Vec::new();
+
+    loop {
+        let x = lexer.next_token();
+        match x.kind {
+            TokenKind::Eof => return tokens,
+            TokenKind::Space => (),
+            _ => tokens.push(x),
+        }
+    }
+}
+
+#[test]
+fn lexes_raw_strings_with_user_delimiters() {
+    let src = r#"r"(C:\dir)" r"sql(SELECT ")" FROM t)sql" r"x(a)y" b)x""#;
+    let contents: Vec<_> = tokens(src).into_iter().map(|x| x.content).collect();
+    assert_eq!(contents, [r"C:\dir", r#"SELECT ")" FROM t"#, r#"a)y" b"#]);
+
+    let [x] = &tokens(r#"r"sql(SELECT 1)lqs""#)[..] else {
+        panic!();
+    };
+    assert_eq!(
+        x.kind,
+        TokenKind::Literal {
+            kind: LiteralKind::RawStr {
+                err: Some(RawStrError::MismatchedDelimiter {
+                    expected: "sql".into(),
+                    found: "lqs".into(),
+                }),
+            },
+            suffix: "".into(),
+        }
+    );
+    assert_eq!(
+        diagnostics(r#"r"sql(SELECT 1)lqs""#),
+        ["1:1: unterminated raw string literal (reached the end of file while looking for the closing quote)"]
+    );
+}
+
+#[test]
+fn strips_the_indentation_of_text_blocks() {
+    let src =
+        "let help = \"\"\"\n    usage: fire <command>\n\n      check   check a file\n    \"\"\";";
+    let lit = tokens(src)
+        .into_iter()
+        .find(|x| x.content.starts_with("usage"));
+    assert_eq!(
+        lit.unwrap().content,
+        "usage: fire <command>\n\n  check   check a file"
+    );
+
+    // the text on the quote lines is kept, and "\r\n" is read as "\n".
+    let [x] = &tokens("\"\"\"a\r\n  b\"\"\"")[..] else {
+        panic!();
+    };
+    assert_eq!(x.content, "a\n  b");
+
+    let [x] = &tokens("\"\"\"\n\t\tx\n\t\t\t{1 + 2}\n\t\"\"\"")[..] else {
+        panic!();
+    };
+    assert!(matches!(
+        &x.kind,
+        TokenKind::Literal {
+            kind: LiteralKind::InterpolatedStr { parts, err: None },
+            ..
+        } if parts[0] == StrPart::Str("x\n\t".into())
+    ));
+
+    assert_eq!(
+        diagnostics("\"\"\"\n  never closed\n"),
+        ["1:1: unterminated string literal (reached the end of file while looking for the closing quote)"]
+    );
+}
+
+#[test]
+fn lexes_doc_comments() {
+    let src = "/// Add two numbers.\n////  not a doc\n/** Block\n * doc. */\n/*** not a doc */\n//! not a doc either\n";
+    let docs: Vec<_> = tokens(src)
+        .into_iter()
+        .map(|x| (x.kind, x.content))
+        .collect();
+    assert_eq!(
+        docs,
+        [
+            (TokenKind::DocComment, "Add two numbers.".into()),
+            (TokenKind::LineComment, "".into()),
+            (TokenKind::DocComment, "Block\ndoc.".into()),
+            (TokenKind::BlockComment { expected: true }, "".into()),
+            (TokenKind::LineComment, "".into()),
+        ]
+    );
+}
+
+#[test]
+fn gives_crlf_files_the_positions_of_lf_files() {
+    let positions = |src: &str| -> Vec<(usize, usize)> {
+        tokens(src).iter().map(|x| (x.line, x.column)).collect()
+    };
+
+    let lf = "let a = 1;\n/* x\n */ let s = \"\"\"\n  b\n\"\"\";\n";
+    let crlf = lf.replace('\n', "\r\n");
+    assert_eq!(positions(&crlf), positions(lf));
+
+    // the text of the literals doesn't depend on the line endings either.
+    let contents = |src: &str| -> Vec<String> {
+        tokens(src)
+            .into_iter()
+            .map(|x| x.content.into_owned())
+            .collect()
+    };
+    assert_eq!(contents(&crlf), contents(lf));
+}
+
+#[test]
+fn skips_the_shebang_and_the_bom() {
+    let src = "\u{FEFF}#!/usr/bin/env -S fire check\r\nlet a = 1;";
+    let tokens = tokens(src);
+
+    assert_eq!(tokens[0].kind, TokenKind::Shebang);
+    assert_eq!(tokens[0].content, "#!/usr/bin/env -S fire check");
+    assert_eq!((tokens[1].line, tokens[1].column), (2, 1));
+
+    // the BOM takes no column.
+    let [x] = &self::tokens("\u{FEFF}a")[..] else {
+        panic!();
+    };
+    assert_eq!((x.line, x.column), (1, 1));
+
+    // a "#!" after the first line is not a shebang.
+    assert!(!diagnostics("let a = 1;\n#!/bin/sh").is_empty());
+}
+
+#[test]
+fn reports_the_invalid_byte_literals() {
+    assert_eq!(
+        diagnostics("b'é' b\"caf\\u{e9}\" b'ab' b'' b\"é\""),
+        [
+            "1:1: non-ASCII character in byte literal, use a '\\x' escape sequence instead",
+            "1:6: unicode escape sequence cannot be used in byte literal",
+            "1:19: byte literal may only contain one byte \
+             (if you meant to write a byte string literal, use double quotes: b\"ab\")",
+            "1:25: empty byte literal",
+            "1:29: non-ASCII character in byte literal, use a '\\x' escape sequence instead",
+        ]
+    );
+
+    // unlike the strings, the bytes go up to 0xFF.
+    assert!(diagnostics(r#"b'\xff' b"\x00\xff\n" b'a'"#).is_empty());
+}
+
+#[test]
+fn reports_the_invalid_escapes_of_every_literal() {
+    let long = "x".repeat(17);
+    let src = format!(
+        "'\\q' \"a\\qb\" \"{{a\" \"a}}b\" \"{{ }}\" r\"{long}(a){long}\" r\"x\" \"ok\\n{{1}}\""
+    );
+
+    assert_eq!(
+        diagnostics(&src),
+        [
+            "1:1: there are some illegal characters in the escape sequence",
+            "1:6: there are some illegal characters in the escape sequence",
+            "1:13: there is not a '}' to close the embedded expression, use '{{' for a literal '{'",
+            "1:18: unmatched '}' in the string literal, use '}}' for a literal '}'",
+            "1:24: empty expression in the string literal, use '{{}}' for literal braces",
+            "1:30: the delimiter of the raw string literal cannot be more than 16 characters",
+            "1:71: missed a parentheses in the raw string literal",
+        ]
+    );
+}
+
+#[test]
+fn reads_the_integers_in_their_base() {
+    let lit = |src: &str| {
+        let mut parser = Parser::new(Lexer::new(src));
+        let x = parser
+            .parse_primary()
+            .map(|x| parser.exprs()[x].kind.clone());
+        assert!(
+            parser.diagnostics().is_empty(),
+            "{:?}",
+            parser.diagnostics()
+        );
+        x.unwrap()
+    };
+
+    assert_eq!(
+        lit("0xFF"),
+        Expression::Literal(Literal::UnsuffixedInt(255))
+    );
+    assert_eq!(lit("0o17"), Expression::Literal(Literal::UnsuffixedInt(15)));
+    assert_eq!(lit("0b1010u8"), Expression::Literal(Literal::UByte(10)));
+    assert_eq!(
+        lit("0xffffu16"),
+        Expression::Literal(Literal::UInt16(u16::MAX))
+    );
+    assert_eq!(
+        lit("340282366920938463463374607431768211455u128"),
+        Expression::Literal(Literal::UInt128(u128::MAX))
+    );
+
+    assert_eq!(
+        diagnostics("300u8 0x1_i8 0b102 0o78 99999999999999999999999999999999999999999"),
+        [
+            "1:1: literal out of range for `uint8` (the maximum value of `uint8` is 255)",
+            "1:7: invalid suffix `i8` for integer literal `0x1` (the suffix must be one of \
+             `b`, `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64`, `i128`, `u128`)",
+            "1:14: invalid digit `2` in binary literal `0b102`",
+            "1:20: invalid digit `8` in octal literal `0o78`",
+            "1:25: integer literal `99999999999999999999999999999999999999999` is too large \
+             (the maximum value of an integer is 2^128 - 1)",
+        ]
+    );
+}
+
+#[test]
+fn reports_the_floats_in_another_base() {
+    assert_eq!(
+        diagnostics("0x1.5 0b1e5 0o7.5f32"),
+        [
+            "1:1: hexadecimal float literals are not supported",
+            "1:7: binary float literals are not supported",
+            "1:13: octal float literals are not supported",
+        ]
+    );
+
+    // the literals are invalid, but they don't stop the parsing.
+    for src in ["let x = 0x1.5;", "let x = 0b1e5;", "let x = 0o7.5f32;"] {
+        let mut parser = Parser::new(Lexer::new(src));
+        let module = parser.parse_module();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+        let Statement::VariableDecl { value, .. } = &module.items[0].kind else {
+            panic!("{:?}", module.items[0]);
+        };
+        assert_eq!(
+            module.exprs[*value].kind,
+            Expression::Literal(Literal::Unexpected("invalid float literal".into()))
+        );
+    }
+}
+
+#[test]
+fn reports_the_floats_too_large() {
+    assert_eq!(
+        diagnostics("1e999 1e39f32 1e300 1e39f64"),
+        [
+            "1:1: literal out of range for `float64` \
+             (the maximum value of `float64` is 1.7976931348623157e308)",
+            "1:7: literal out of range for `float32` (the maximum value of `float32` is 3.4028235e38)",
+        ]
+    );
+}
+
+#[test]
+fn warns_about_the_identifiers_of_mixed_scripts() {
+    // the first letter is the cyrillic "а".
+    assert_eq!(
+        diagnostics("let аbc = 1;"),
+        [
+            "1:5: identifier `аbc` mixes characters of different scripts \
+             (some of them look like the characters of another script)"
+        ]
+    );
+
+    assert!(diagnostics("let абв = 1; let abc = 2;").is_empty());
+}
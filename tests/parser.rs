@@ -0,0 +1,222 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::node::{
+    Expr, Expression, Exprs, NodeId, Statement, Stmt,
+};
+use firelang::compiler::firelang_parser::ast::visit::{self, Visitor};
+use firelang::compiler::firelang_parser::parser::Parser;
+
+fn kinds(stmts: &[Stmt]) -> Vec<&Statement> {
+    stmts.iter().map(|x| &x.kind).collect()
+}
+
+#[test]
+fn reports_every_syntax_error() {
+    let src = r#"fn add(a <- int32, b <- int32) {
+    let c = ;
+    return c
+}
+let x = 1 let y = 2;
+}
+fn g(a b) { let z = 1; }
+fn ok() { return 1; }
+"#;
+
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+
+    // the errors are at the start of the tokens found instead of the expected ones.
+    let positions: Vec<_> = parser.errors().iter().map(|x| (x.line, x.column)).collect();
+    assert_eq!(positions, [(2, 13), (4, 1), (5, 11), (6, 1), (7, 8)]);
+
+    let Statement::FuncDecl { body, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    assert!(matches!(
+        kinds(&body.block)[..],
+        [Statement::VariableDecl { value, .. }, Statement::Error]
+            if module.exprs[*value].kind == Expression::Error
+    ));
+
+    // the statements after the errors are parsed as usual.
+    assert!(matches!(
+        kinds(&module.items)[1..],
+        [
+            Statement::Error,
+            Statement::VariableDecl { .. },
+            Statement::Error,
+            Statement::Error,
+            Statement::FuncDecl { .. }
+        ]
+    ));
+}
+
+#[test]
+fn lists_the_expected_tokens() {
+    let src = r#"let x: int32 }
+let y
+fn f(a, b <- int32) {}
+let z = (1 + 2;
+return 1 + ;
+{ let w = 1;
+"#;
+
+    let mut parser = Parser::new(Lexer::new(src));
+    parser.parse_module();
+
+    let messages: Vec<(usize, &str)> = parser
+        .errors()
+        .iter()
+        .map(|x| (x.line, x.message.as_str()))
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            (1, "expected one of `=`, `<-`, `->`, found `}`"),
+            (1, "expected one of `fn`, `let`, `{`, `return`, found `}`"),
+            (3, "expected one of `:`, `=`, `<-`, `->`, found `fn`"),
+            (3, "expected one of `=`, `<-`, `->`, found `,`"),
+            (4, "expected one of <operator>, `)`, found `;`"),
+            (5, "expected one of <literal>, <identifier>, `(`, found `;`"),
+            (7, "expected `}`, found end of file"),
+        ]
+    );
+}
+
+/// Collect the ids of the nodes.
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl Visitor for Ids {
+    fn visit_stmt(&mut self, exprs: &Exprs, stmt: &Stmt) {
+        self.0.push(stmt.id);
+        visit::walk_stmt(self, exprs, stmt);
+    }
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: &Expr) {
+        self.0.push(expr.id);
+        visit::walk_expr(self, exprs, expr);
+    }
+}
+
+#[test]
+fn numbers_every_node_once() {
+    let src = r#"fn f(a <- int32) {
+    let s = "a = {a + g(1, 2)}";
+    if a { return (a - 1) * 2; }
+}
+let x = f(3);
+"#;
+
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+
+    let mut ids = Ids::default();
+    ids.visit_module(&module);
+    ids.0.sort();
+
+    let count = parser.node_ids().count();
+    assert_eq!(ids.0, (0..count as u32).map(NodeId).collect::<Vec<_>>());
+
+    // the children are numbered before their parent.
+    let Statement::VariableDecl { value, .. } = &module.items[1].kind else {
+        panic!("{:?}", module.items[1]);
+    };
+    let value = &module.exprs[*value];
+    let Expression::FuncCall { args, .. } = &value.kind else {
+        panic!("{value:?}");
+    };
+    assert!(module.exprs[args[0]].id < value.id && value.id < module.items[1].id);
+}
+
+#[test]
+fn puts_the_expressions_in_the_arena() {
+    let src = "let s = \"a = {a + g(1, 2)}\";\nreturn (a - 1) * 2;\n";
+    let module = Parser::new(Lexer::new(src)).parse_module();
+
+    // `a`, `1`, `2`, the call, the sum and the string, then `a`, `1`, the
+    // difference, `2` and the product.
+    assert_eq!(module.exprs.len(), 11);
+
+    let Statement::Return(x) = &module.items[1].kind else {
+        panic!("{:?}", module.items[1]);
+    };
+    // the parent is put after its children.
+    assert_eq!(x.index(), 10);
+}
+
+#[test]
+fn attaches_the_docs_to_the_declarations() {
+    let src = r#"/// Add
+/// two numbers.
+fn add(a <- int32, b <- int32) {
+    /** The sum. */
+    let c = a + b;
+    /// Dropped, a return has no doc.
+    return c;
+}
+// a comment between the doc and the declaration.
+/// The answer.
+// the doc still belongs to it.
+let x = 42;
+let y = 1;
+"#;
+
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    let doc = |x: &Stmt| match &x.kind {
+        Statement::FuncDecl { doc, .. } | Statement::VariableDecl { doc, .. } => doc.clone(),
+        _ => None,
+    };
+
+    let Statement::FuncDecl { body, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    assert_eq!(doc(&module.items[0]).as_deref(), Some("Add\ntwo numbers."));
+    assert_eq!(doc(&body.block[0]).as_deref(), Some("The sum."));
+    assert_eq!(doc(&module.items[1]).as_deref(), Some("The answer."));
+    assert_eq!(doc(&module.items[2]), None);
+}
+
+/// The lints of parsing the source, with their notes.
+fn lints(src: &str) -> Vec<String> {
+    let mut parser = Parser::new(Lexer::new(src));
+    parser.parse_module();
+
+    parser
+        .lints()
+        .iter()
+        .map(|x| {
+            format!(
+                "{}:{}: {} ({})",
+                x.line,
+                x.column,
+                x.message,
+                x.notes.join(", ")
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn warns_about_the_confusable_identifiers() {
+    // the first one is the cyrillic "а".
+    assert_eq!(
+        lints("let а = 1; let a = 2;"),
+        ["1:16: identifier `a` is confusable with `а` (`а` is declared at line 1, col 5)"]
+    );
+
+    // in the inner scopes, against the outer names too.
+    assert_eq!(
+        lints("let о = 1;\nfn f(o <- int32) { let о = 2; }").len(),
+        2
+    );
+
+    assert!(lints("let a = 1; let b = 2;").is_empty());
+    // the ASCII names are told apart, and a name looks like itself.
+    assert!(lints("let rn = 1; let m = 2; let a = 1; let a = 2;").is_empty());
+    // the scopes which have ended are not visible.
+    assert!(lints("fn f() { let а = 1; }\nlet a = 2;").is_empty());
+}
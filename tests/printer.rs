@@ -0,0 +1,150 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::node::Module;
+use firelang::compiler::firelang_parser::ast::printer::Printer;
+use firelang::compiler::firelang_parser::parser::{Layout, Parser};
+
+/// Format `src`, or `None` if it has errors.
+fn format(src: &str) -> Option<(Module, String)> {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+
+    if parser.diagnostics().has_errors() || parser.errors().has_errors() {
+        return None;
+    }
+
+    let formatted = Printer::new(src, parser.layout()).print_module(&module);
+    Some((module, formatted))
+}
+
+#[test]
+fn prints_the_canonical_style() {
+    let src = r#"// leading comment
+
+
+/// Adds.
+fn   add( a<-int32 ,b->int32,c=int32){let   mut x:int32=a+b*c ;   // trailing
+  let y <- (a+b)*c;let z=a-(b-c); let w = (a-b)-c;
+   /* block
+      comment */
+      return x+3   +y;
+      // last in block
+}
+fn empty() {   }
+fn commented() {
+// only a comment
+}
+
+
+let s = "a {x + 1} \t b";    let r = r"(raw "str")";
+{ let q = 1.5_f64; let t = 'c'; let u = 12_u8; }
+return f(1,g(2, 3),  "x\n");
+// eof comment
+"#;
+
+    let expected = r#"// leading comment
+
+/// Adds.
+fn add(a <- int32, b -> int32, c = int32) {
+    let mut x: int32 = a + b * c; // trailing
+    let y <- (a + b) * c;
+    let z = a - (b - c);
+    let w = a - b - c;
+    /* block
+      comment */
+    return x + 3 + y;
+    // last in block
+}
+
+fn empty() {}
+
+fn commented() {
+    // only a comment
+}
+
+let s = "a {x + 1} \t b";
+let r = r"(raw "str")";
+{
+    let q = 1.5_f64;
+    let t = 'c';
+    let u = 12_u8;
+}
+return f(1, g(2, 3), "x\n");
+// eof comment
+"#;
+
+    let (_, formatted) = format(src).unwrap();
+    assert_eq!(formatted, expected);
+}
+
+/// Formatting the files twice changes nothing, and keeps the tree.
+#[test]
+fn formats_the_test_files_idempotently() {
+    let mut formatted_files = 0;
+
+    for entry in std::fs::read_dir("test").unwrap() {
+        let path = entry.unwrap().path();
+        let src = std::fs::read_to_string(&path).unwrap();
+
+        // the files for the lexer are not valid programs.
+        let Some((module, once)) = format(&src) else {
+            continue;
+        };
+
+        let (reparsed, twice) = format(&once).unwrap();
+        assert_eq!(once, twice, "{}", path.display());
+        assert_eq!(module, reparsed, "{}", path.display());
+
+        formatted_files += 1;
+    }
+
+    assert!(formatted_files >= 2);
+}
+
+#[test]
+fn prints_a_tree_without_source() {
+    let src = r#"/// The sum.
+fn f(a = int64) {
+    let b = a * (2_i64 + 3_i64);
+    let c: str = "{b} \x22quoted\x22 {{ }}\n";
+    let d = r"(C:\\dir)" + 'x' + '\'' + 0.5 + 7_u16;
+    return g(b, c, d);
+}
+"#;
+
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    let printed = Printer::new("", &Layout::default()).print_module(&module);
+
+    let (reparsed, _) = format(&printed).unwrap();
+    assert_eq!(module, reparsed, "{printed}");
+}
+
+#[test]
+fn keeps_the_comments_inside_a_statement_where_they_are() {
+    let src = r#"let   x = 1 /* keep */ + 2;
+fn f(a = int32 /* p */)   {let y=1;}
+fn   g() { return f(1, // one
+        2); }
+let z=3;
+"#;
+
+    let expected = r#"let   x = 1 /* keep */ + 2;
+
+fn f(a = int32 /* p */) {
+    let y = 1;
+}
+
+fn g() {
+    return f(1, // one
+        2);
+}
+
+let z = 3;
+"#;
+
+    let (_, formatted) = format(src).unwrap();
+    assert_eq!(formatted, expected);
+
+    let (_, twice) = format(&formatted).unwrap();
+    assert_eq!(twice, expected);
+}
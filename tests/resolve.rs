@@ -0,0 +1,140 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::node::{Expression, Module, Statement};
+use firelang::compiler::firelang_parser::ast::token::Literal;
+use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::compiler::firelang_sema::resolve::{Res, Resolutions, Resolver};
+
+fn resolve(src: &str) -> (Module, Resolutions) {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    let resolutions = Resolver::new(parser.positions()).resolve_module(&module);
+    (module, resolutions)
+}
+
+fn errors(src: &str) -> Vec<String> {
+    resolve(src)
+        .1
+        .errors
+        .iter()
+        .map(|x| {
+            let mut s = format!("{}:{}: {}", x.line, x.column, x.message);
+            for note in &x.notes {
+                s += &format!(" ({note})");
+            }
+            s
+        })
+        .collect()
+}
+
+#[test]
+fn resolves_every_name() {
+    let src = r#"fn f(a <- int32) {
+    let b = a + 1;
+    let a = g(b);
+    return "{a}";
+}
+fn g(x = int32) { return f(x); }
+"#;
+
+    let (module, resolutions) = resolve(src);
+    assert!(resolutions.errors.is_empty(), "{:?}", resolutions.errors);
+
+    let f = module.items[0].id;
+    let Statement::FuncDecl { body, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    let (b, a) = (body.block[0].id, body.block[1].id);
+
+    let Statement::VariableDecl { value, .. } = &body.block[0].kind else {
+        panic!("{:?}", body.block[0]);
+    };
+    let value = &module.exprs[*value];
+    let Expression::Binary { lhs, .. } = &value.kind else {
+        panic!("{value:?}");
+    };
+    assert_eq!(resolutions.names[&module.exprs[*lhs].id], Res::Param(f, 0));
+
+    // `g` is declared after it is called.
+    let Statement::VariableDecl { value, .. } = &body.block[1].kind else {
+        panic!("{:?}", body.block[1]);
+    };
+    let value = &module.exprs[*value];
+    let Expression::FuncCall { args, .. } = &value.kind else {
+        panic!("{value:?}");
+    };
+    assert_eq!(resolutions.names[&value.id], Res::Func(module.items[1].id));
+    assert_eq!(resolutions.names[&module.exprs[args[0]].id], Res::Local(b));
+
+    // the shadowing `a` in the interpolated string.
+    let names: Vec<_> = resolutions.names.values().collect();
+    assert!(names.contains(&&Res::Local(a)));
+    assert_eq!(resolutions.names.len(), 6);
+}
+
+#[test]
+fn reports_the_unknown_names() {
+    let src = r#"fn main(count <- int32) {
+    let total = 1;
+    { let inner = 2; }
+    let a = totl + inner;
+    let b = undefined;
+    let c = mian(count);
+    let d = d;
+    return io::println("{cont}");
+}
+"#;
+
+    assert_eq!(
+        errors(src),
+        [
+            "4:13: cannot find value `totl` in this scope (did you mean `total`?)",
+            "4:20: cannot find value `inner` in this scope",
+            "5:13: cannot find value `undefined` in this scope",
+            "6:13: cannot find function `mian` in this scope (did you mean `main`?)",
+            "7:13: cannot find value `d` in this scope (did you mean `a`?)",
+            "8:26: cannot find value `cont` in this scope (did you mean `count`?)",
+        ]
+    );
+}
+
+#[test]
+fn reports_the_names_where_they_start() {
+    assert_eq!(
+        errors("let x = undefined_name;"),
+        ["1:9: cannot find value `undefined_name` in this scope"]
+    );
+
+    // the nodes are where their first token starts, on its line.
+    let src = "let x = 1;\nlet total = x +\n    \"multi\nline\";";
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+
+    let Statement::VariableDecl { value, .. } = &module.items[1].kind else {
+        panic!("{:?}", module.items[1]);
+    };
+    let value = &module.exprs[*value];
+    let Expression::Binary { rhs, .. } = &value.kind else {
+        panic!("{value:?}");
+    };
+    assert_eq!(parser.positions()[&module.items[1].id], (2, 1));
+    assert_eq!(parser.positions()[&value.id], (2, 13));
+    assert_eq!(parser.positions()[&module.exprs[*rhs].id], (3, 5));
+}
+
+#[test]
+fn the_booleans_are_not_names() {
+    let src = "let b: bool = true;\nlet c = false;\n";
+    assert!(errors(src).is_empty());
+
+    let (module, resolutions) = resolve(src);
+    let Statement::VariableDecl { value, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    assert_eq!(
+        module.exprs[*value].kind,
+        Expression::Literal(Literal::Boolean(true))
+    );
+    assert!(resolutions.names.is_empty());
+}
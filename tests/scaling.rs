@@ -0,0 +1,67 @@
+use firelang::compiler::firelang_lexer::lexer::{Lexer, TokenKind::Eof};
+use firelang::compiler::firelang_parser::parser::Parser;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SNIPPET: &str = r#"
+    // compute something
+    /* nested /* block */ comment */
+    let a = 31 + 10 * (3 - 1.5e3);
+    let s = "hello\tworld, 中文 {a}";
+    let c = '\u{1F600}';
+    return a;
+"#;
+
+fn source(size: usize) -> String {
+    let mut src = String::from("fn main() {");
+    while src.len() < size {
+        src.push_str(SNIPPET);
+    }
+    src.push('}');
+    src
+}
+
+/// The shortest of a few runs, the others may have been slowed down by the
+/// other tests.
+fn time(src: &str, f: fn(&str)) -> Duration {
+    (0..3)
+        .map(|_| {
+            let begin = Instant::now();
+            f(black_box(src));
+            begin.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Eight times the input must take about eight times as long, a quadratic
+/// lexer or parser would take 64 times as long.
+fn assert_linear(f: fn(&str)) {
+    let (small, large) = (source(128 << 10), source(1 << 20));
+    let ratio = time(&large, f).as_secs_f64() / time(&small, f).as_secs_f64();
+
+    assert!(
+        ratio < 24.0,
+        "8 times the input took {ratio:.1} times as long"
+    );
+}
+
+// the times depend on the load of the machine, run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn lexes_in_linear_time() {
+    assert_linear(|src| {
+        let mut lexer = Lexer::new(src);
+        while lexer.next_token().kind != Eof {}
+    });
+}
+
+#[test]
+#[ignore]
+fn parses_in_linear_time() {
+    assert_linear(|src| {
+        let mut parser = Parser::new(Lexer::new(src));
+        black_box(parser.parse_module());
+        assert!(parser.errors().is_empty());
+    });
+}
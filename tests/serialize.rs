@@ -0,0 +1,223 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::json;
+use firelang::compiler::firelang_parser::ast::node::{
+    Behaviour, Block, Expr, Expression, Exprs, Module, NodeId, Statement, Stmt,
+};
+use firelang::compiler::firelang_parser::ast::sexp::Sexp;
+use firelang::compiler::firelang_parser::ast::token::Literal;
+use firelang::compiler::firelang_parser::ast::visit::{self, Visitor};
+use firelang::compiler::firelang_parser::parser::Parser;
+
+const SOURCE: &str = r#"/// Adds "two" numbers.
+fn add(a <- int32, b -> int64, c = str) {
+    let mut x: int32 = a + b * (c - 1);
+    let s = "a + b = {a + b}, {"nested"} \u{1F525}";
+    let r = r"(C:\dir)";
+    { return f(x, g(), 'c', 2_i64, 1.5_f64, 255_u128); }
+}
+let y = add(1, 2, "3");
+let z = ;
+return;
+"#;
+
+fn parse(src: &str) -> Module {
+    Parser::new(Lexer::new(src)).parse_module()
+}
+
+/// The literals which the parser doesn't make.
+fn literals() -> Module {
+    let values = [
+        Literal::Byte(-8),
+        Literal::Int16(-16),
+        Literal::Int(i32::MIN),
+        Literal::Int64(i64::MAX),
+        Literal::Int128(i128::MIN),
+        Literal::UByte(8),
+        Literal::UInt16(16),
+        Literal::UInt(32),
+        Literal::UInt64(u64::MAX),
+        Literal::UInt128(u128::MAX),
+        Literal::UnsuffixedInt(u128::MAX),
+        Literal::Float(0.1),
+        Literal::Float64(1e-300),
+        Literal::UnsuffixedFloat(1e300),
+        Literal::Char('"'),
+        Literal::Boolean(true),
+        Literal::Str("tab\t \\ \u{0} 中文".into()),
+        Literal::ByteStr(vec![0, 34, 255]),
+        Literal::Unexpected("empty character literal".into()),
+    ];
+
+    let mut exprs = Exprs::default();
+
+    let args = values
+        .into_iter()
+        .map(|x| exprs.alloc(Expression::Literal(x).into()))
+        .collect();
+    let call = exprs.alloc(
+        Expression::FuncCall {
+            ident: "f".into(),
+            args,
+        }
+        .into(),
+    );
+    let cond = exprs.alloc(Expression::Ident("mut".into()).into());
+    let value = exprs.alloc(Expression::None.into());
+
+    Module {
+        items: vec![Statement::If {
+            cond,
+            block: Block {
+                block: vec![Statement::Return(call).into()],
+            },
+            els: Some(Block {
+                block: vec![Statement::VariableDecl {
+                    doc: None,
+                    ident: "mut".into(),
+                    ty: "".into(),
+                    mutable: true,
+                    behaviour: Behaviour::Ref,
+                    value,
+                }
+                .into()],
+            }),
+        }
+        .into()],
+        exprs,
+        span: Default::default(),
+    }
+}
+
+#[test]
+fn json_round_trip() {
+    // the floats too large are invalid literals, not infinities, which JSON hasn't.
+    for module in [parse(SOURCE), literals(), parse("let x = 1e999;\n")] {
+        let text = json::to_string(&module);
+        let back = json::from_str(&text).unwrap();
+
+        assert_eq!(module, back, "{text}");
+    }
+}
+
+#[test]
+fn sexp_round_trip() {
+    for module in [parse(SOURCE), literals()] {
+        let text = Sexp::from(&module).to_string();
+        let sexp = Sexp::parse(&text).unwrap();
+        let back = Module::try_from(&sexp).unwrap();
+
+        assert_eq!(module, back, "{text}");
+    }
+}
+
+#[test]
+fn writes_the_schema() {
+    let module = parse("/// Doc.\nlet mut x: int64 <- f(a, 1) + 2_i64;\n");
+
+    assert_eq!(
+        json::to_string(&module),
+        r#"{"version":1,"kind":"Module","items":[{"kind":"VariableDecl","doc":"Doc.","ident":"x","type":"int64","mutable":true,"behaviour":"<-","value":{"kind":"Binary","lhs":{"kind":"FuncCall","ident":"f","args":[{"kind":"Ident","name":"a"},{"kind":"Literal","type":"int","value":"1"}]},"op":"+","rhs":{"kind":"Literal","type":"int64","value":2}}}]}"#
+    );
+
+    // the 128-bit integers are strings.
+    let wide = parse("return 1_i128 + 340282366920938463463374607431768211455_u128;");
+    assert!(json::to_string(&wide).contains(
+        r#"{"kind":"Literal","type":"int128","value":"1"},"op":"+","rhs":{"kind":"Literal","type":"uint128","value":"340282366920938463463374607431768211455"}"#
+    ));
+
+    assert_eq!(
+        Sexp::from(&module).to_string(),
+        r#"(module (let mut x int64 <- (+ (call f a 1) (int64 2)) (doc "Doc.")))"#
+    );
+}
+
+#[test]
+fn reads_hand_written_input() {
+    let json = json::from_str(
+        r#" { "items" : [ { "value" :
+            { "value" : "\ud83d\udd25\n", "type" : "str", "kind" : "Literal" }, "kind" : "Return" } ],
+            "version" : 1 } "#,
+    )
+    .unwrap();
+
+    let sexp = Sexp::parse("(module\n  (return \"\\ud83d\\udd25\\n\"))").unwrap();
+
+    let mut exprs = Exprs::default();
+    let value = exprs.alloc(Expression::Literal(Literal::Str("\u{1F525}\n".into())).into());
+    let expected = Module {
+        items: vec![Statement::Return(value).into()],
+        exprs,
+        span: Default::default(),
+    };
+
+    assert_eq!(json, expected);
+    assert_eq!(Module::try_from(&sexp).unwrap(), expected);
+}
+
+#[test]
+fn reports_invalid_input() {
+    assert_eq!(
+        json::from_str(r#"{"version": 1, "items": [}"#).unwrap_err(),
+        "expected value at line 1 column 26"
+    );
+    assert!(
+        json::from_str(r#"{"version": 1, "items": [{"kind": "Loop"}]}"#)
+            .unwrap_err()
+            .starts_with("unknown variant `Loop`, expected one of `Block`, `FuncDecl`")
+    );
+    assert_eq!(
+        json::from_str(r#"{"version": 2, "items": []}"#).unwrap_err(),
+        "unsupported version 2, the version of the format is 1"
+    );
+    assert_eq!(
+        json::from_str(r#"{"items": []}"#).unwrap_err(),
+        "missing field `version` at line 1 column 13"
+    );
+    assert_eq!(
+        Sexp::parse("(module (return 1)").unwrap_err(),
+        "expected `)`, found end of input"
+    );
+    assert_eq!(
+        Module::try_from(&Sexp::parse("(module (return (int8 300)))").unwrap()).unwrap_err(),
+        "invalid value `300`"
+    );
+}
+
+/// The ids of the nodes, in the order they are visited.
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl Visitor for Ids {
+    fn visit_stmt(&mut self, exprs: &Exprs, stmt: &Stmt) {
+        self.0.push(stmt.id);
+        visit::walk_stmt(self, exprs, stmt);
+    }
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: &Expr) {
+        self.0.push(expr.id);
+        visit::walk_expr(self, exprs, expr);
+    }
+}
+
+#[test]
+fn numbers_the_nodes_read_back_like_the_parser() {
+    let src = r#"fn add(a <- int32, b -> int64) {
+    let x: int32 = a + b * (a - 1);
+    let s = "a + b = {a + f(b)}";
+    { return f(x, g(), 'c', 2_i64); }
+}
+let y = add(1, 2);
+"#;
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    let ids = |module: &Module| {
+        let mut ids = Ids::default();
+        ids.visit_module(module);
+        ids.0
+    };
+
+    let back = json::from_str(&json::to_string(&module)).unwrap();
+    assert_eq!(ids(&back), ids(&module));
+}
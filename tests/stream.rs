@@ -0,0 +1,103 @@
+use firelang::compiler::firelang_lexer::lexer::{Lexer, Token, TokenKind};
+use firelang::compiler::firelang_lexer::stream::{StreamLexer, CHUNK_SIZE};
+use std::io::Read;
+
+/// A reader giving one byte at a time, which splits every UTF-8 sequence.
+struct OneByte<'a>(&'a [u8]);
+
+impl Read for OneByte<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.split_first() {
+            Some((x, rest)) if !buf.is_empty() => {
+                buf[0] = *x;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+fn lex(src: &str) -> (Vec<Token<'static>>, Vec<String>) {
+    let mut lexer = Lexer::new(src);
+    let mut tokens = Vec::new();
+
+    loop {
+        let x = lexer.next_token().into_owned();
+        let eof = x.kind == TokenKind::Eof;
+        tokens.push(x);
+
+        if eof {
+            let diagnostics = lexer.diagnostics.iter().map(|x| x.to_string()).collect();
+            return (tokens, diagnostics);
+        }
+    }
+}
+
+fn lex_stream(src: &str) -> (Vec<Token<'static>>, Vec<String>) {
+    let mut lexer = StreamLexer::new(OneByte(src.as_bytes()));
+    let mut tokens = Vec::new();
+
+    loop {
+        let x = lexer.next_token();
+        let eof = x.kind == TokenKind::Eof;
+        tokens.push(x);
+
+        if eof {
+            let diagnostics = lexer.diagnostics.iter().map(|x| x.to_string()).collect();
+            return (tokens, diagnostics);
+        }
+    }
+}
+
+/// `text` placed at each offset around the end of the first chunk.
+fn across_the_chunks(text: &str) -> impl Iterator<Item = String> + '_ {
+    (0..text.len() + 8).map(move |x| {
+        let mut src = "a ".repeat((CHUNK_SIZE - x) / 2);
+        src.push_str(text);
+        src.push_str("\nlet end = 1;");
+        src
+    })
+}
+
+#[test]
+fn gives_the_tokens_of_the_lexer() {
+    let src = r#"fn main(n <- int32) {
+    /* nested /* block */ comment */
+    let s = "a + b = {a + b}";
+    let r = r"sql(SELECT ")" FROM t)sql";
+    return 0x1F + 'c' + 1.5e3;
+}
+"#;
+
+    let (tokens, diagnostics) = lex(src);
+    assert_eq!(lex_stream(src), (tokens, diagnostics));
+}
+
+#[test]
+fn decodes_the_chars_split_across_the_chunks() {
+    for src in across_the_chunks("let s = \"中文 😀 é\"; let 变量 = '😀';") {
+        assert_eq!(lex_stream(&src), lex(&src));
+    }
+}
+
+#[test]
+fn finds_the_raw_string_delimiters_across_the_chunks() {
+    for src in across_the_chunks(r#"r"delim(x )delim" y)delim" r"d(unclosed)e""#) {
+        let (tokens, diagnostics) = lex(&src);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(lex_stream(&src), (tokens, diagnostics));
+    }
+}
+
+#[test]
+fn reports_the_invalid_utf8() {
+    let mut lexer = StreamLexer::new(OneByte(b"let a = \xff;"));
+    while lexer.next_token().kind != TokenKind::Eof {}
+
+    let messages: Vec<_> = lexer.diagnostics.iter().map(|x| &x.message).collect();
+    assert_eq!(
+        messages,
+        ["failed to read the input: stream did not contain valid UTF-8"]
+    );
+}
@@ -0,0 +1,51 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_lexer::symbol::Symbol;
+use firelang::compiler::firelang_parser::ast::node::{Expression, Statement};
+use firelang::compiler::firelang_parser::parser::Parser;
+
+#[test]
+fn interns_each_name_once() {
+    let a = Symbol::intern("alpha");
+    let b = Symbol::from(String::from("alpha"));
+    let c = Symbol::intern("beta");
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(a.as_str(), "alpha");
+    assert_eq!(c.to_string(), "beta");
+    assert_eq!(format!("{a:?}"), "\"alpha\"");
+    assert!(Symbol::intern("").is_empty());
+
+    // ordered by the names, not by the interning.
+    let mut names = vec![Symbol::intern("zeta"), c, a];
+    names.sort();
+    assert_eq!(names, [a, c, Symbol::intern("zeta")]);
+}
+
+#[test]
+fn shares_the_symbols_of_the_tree() {
+    let mut parser = Parser::new(Lexer::new("fn f(x <- int32) { return f(x); }"));
+    let module = parser.parse_module();
+
+    let Statement::FuncDecl {
+        ident,
+        params,
+        body,
+        ..
+    } = &module.items[0].kind
+    else {
+        panic!("not a function: {:?}", module.items);
+    };
+
+    let Statement::Return(x) = &body.block[0].kind else {
+        panic!("not a return: {:?}", body.block);
+    };
+
+    let Expression::FuncCall { ident: call, args } = &module.exprs[*x].kind else {
+        panic!("not a call: {:?}", module.exprs[*x]);
+    };
+
+    assert_eq!(ident, call);
+    assert_eq!(module.exprs[args[0]].kind, Expression::Ident(params[0].0));
+    assert_eq!(params[0].2, Symbol::intern("int32"));
+}
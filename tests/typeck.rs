@@ -0,0 +1,275 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_parser::ast::node::{Expression, Module, Statement};
+use firelang::compiler::firelang_parser::ast::token::Literal;
+use firelang::compiler::firelang_parser::parser::Parser;
+use firelang::compiler::firelang_sema::resolve::Resolver;
+use firelang::compiler::firelang_sema::typeck::{TypeChecker, Types};
+use firelang::compiler::firelang_sema::types::Type;
+
+fn check(src: &str) -> (Module, Types) {
+    let mut parser = Parser::new(Lexer::new(src));
+    let module = parser.parse_module();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+    let resolutions = Resolver::new(parser.positions()).resolve_module(&module);
+    assert!(resolutions.errors.is_empty(), "{:?}", resolutions.errors);
+
+    let types = TypeChecker::new(parser.positions(), &resolutions).check_module(&module);
+    (module, types)
+}
+
+fn errors(src: &str) -> Vec<String> {
+    check(src)
+        .1
+        .errors
+        .iter()
+        .map(|x| {
+            let mut s = format!("{}:{}: {}", x.line, x.column, x.message);
+            for note in &x.notes {
+                s += &format!(" ({note})");
+            }
+            s
+        })
+        .collect()
+}
+
+#[test]
+fn types_every_expression() {
+    let src = r#"let n = add(1, 2) * 3;
+let s = "a" + name(n);
+let ok = n >= 3 && n < 10;
+fn add(a <- int32, b <- int32) { return a + b; }
+fn name(x <- int32) { return "{x}"; }
+"#;
+
+    let (module, types) = check(src);
+    assert!(types.errors.is_empty(), "{:?}", types.errors);
+
+    let lets: Vec<_> = module.items[..3]
+        .iter()
+        .map(|x| types.types[&x.id])
+        .collect();
+    assert_eq!(lets, [Type::Int32, Type::Str, Type::Bool]);
+
+    // every expression has a type.
+    for x in &module.items[..3] {
+        let Statement::VariableDecl { value, .. } = &x.kind else {
+            panic!("{x:?}");
+        };
+        assert!(types.types.contains_key(&module.exprs[*value].id));
+    }
+}
+
+#[test]
+fn reports_the_mismatched_types() {
+    let src = r#"fn f(a <- int32, b <- str) {
+    let c = a + b;
+    let d = b - b;
+    let e: bool = 1;
+    return a;
+    return b;
+}
+fn g() { let h = f(1); return f(1, 2.0); }
+fn k(x <- num) { return k; }
+"#;
+
+    assert_eq!(
+        errors(src),
+        [
+            // the signatures are checked before the bodies.
+            "9:1: cannot find type `num` in this scope",
+            "2:17: mismatched types: expected `int32`, found `str` (the operands of `+` must have the same type)",
+            "3:13: cannot apply `-` to `str`",
+            "4:19: mismatched types: expected `bool`, found `int32`",
+            "6:12: mismatched types: expected `int32`, found `str` (the return type is set by the `return` at line 5)",
+            "8:18: `f` takes 2 arguments but 1 was given",
+            "8:36: mismatched types: expected `str`, found `float32`",
+            "9:25: expected a value, found function `k`",
+        ]
+    );
+}
+
+#[test]
+fn infers_the_types_of_the_lets_and_the_literals() {
+    let src = r#"let x = 1;
+let y = x + 2_i64;
+let a: int64 = 1 + 2 * 3;
+let b: uint8 = 300;
+fn f(n <- uint16) { return n + 1; }
+let c = 4 + f(5);
+let d = 1i32 + 2u8;
+"#;
+
+    let (module, types) = check(src);
+
+    let lets: Vec<_> = module
+        .items
+        .iter()
+        .map(|x| types.types.get(&x.id))
+        .collect();
+    assert_eq!(
+        lets,
+        [
+            Some(&Type::Int32),
+            Some(&Type::Int32),
+            Some(&Type::Int64),
+            Some(&Type::UInt8),
+            None,
+            Some(&Type::UInt16),
+            Some(&Type::Int32),
+        ]
+    );
+
+    // the literals take the type of the annotation.
+    let Statement::VariableDecl { value, .. } = &module.items[2].kind else {
+        panic!("{:?}", module.items[2]);
+    };
+    let Expression::Binary { lhs, .. } = &module.exprs[*value].kind else {
+        panic!("{value:?}");
+    };
+    assert_eq!(types.types[&module.exprs[*lhs].id], Type::Int64);
+
+    let errors: Vec<_> = types
+        .errors
+        .iter()
+        .map(|x| format!("{}:{}: {}", x.line, x.column, x.message))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "2:13: mismatched types: expected `int32`, found `int64`",
+            "4:16: literal out of range for `uint8`",
+            "7:16: mismatched types: expected `int32`, found `uint8`",
+        ]
+    );
+}
+
+#[test]
+fn reports_the_conflicting_returns() {
+    let src = r#"fn f(a <- int32) {
+    {
+        return a;
+    }
+    return "none";
+}
+fn g() {
+    return 1u8;
+    return 1i64;
+}
+fn h() { return 1; return 2; }
+"#;
+
+    assert_eq!(
+        errors(src),
+        [
+            "5:12: mismatched types: expected `int32`, found `str` (the return type is set by the `return` at line 3)",
+            "9:12: mismatched types: expected `uint8`, found `int64` (the return type is set by the `return` at line 8)",
+        ]
+    );
+}
+
+#[test]
+fn reports_the_value_of_a_function_without_return() {
+    let src = r#"fn log(x <- str) { let y = x; }
+let a = log("a");
+let b: str = log("b");
+fn f() { let z = log("f"); }
+let c = f() + 1;
+"#;
+
+    assert_eq!(
+        errors(src),
+        [
+            "2:9: expected a value, found `()` (`log` has no `return`, so it returns `()`)",
+            "3:14: mismatched types: expected `str`, found `()`",
+            "4:18: expected a value, found `()` (`log` has no `return`, so it returns `()`)",
+            "5:9: cannot apply `+` to `()`",
+        ]
+    );
+}
+
+#[test]
+fn checks_the_arguments_of_the_calls() {
+    let src = r#"fn f(a <- int32, b <- str) { return b; }
+let x = f(1);
+let y = f(1, "b", 3);
+let z = f();
+let w = f("a", 2);
+let v = f(1u8 + 1, "b" + "c");
+"#;
+
+    assert_eq!(
+        errors(src),
+        [
+            "2:9: `f` takes 2 arguments but 1 was given",
+            "3:9: `f` takes 2 arguments but 3 were given",
+            "4:9: `f` takes 2 arguments but 0 were given",
+            "5:11: mismatched types: expected `int32`, found `str`",
+            "5:16: mismatched types: expected `str`, found `int32`",
+            "6:11: mismatched types: expected `int32`, found `uint8`",
+        ]
+    );
+}
+
+#[test]
+fn the_literals_without_suffix_take_the_wide_types() {
+    let src = r#"let x: int64 = 3000000000;
+let y = 3000000000;
+let z: uint128 = 340282366920938463463374607431768211455;
+let f: float64 = 1.5;
+let g = 2.5;
+let h: float64 = 1.5 * 2.0;
+let i: float64 = 1;
+"#;
+
+    let (module, types) = check(src);
+
+    let lets: Vec<_> = module.items.iter().map(|x| types.types[&x.id]).collect();
+    assert_eq!(
+        lets,
+        [
+            Type::Int64,
+            Type::Int32,
+            Type::UInt128,
+            Type::Float64,
+            Type::Float32,
+            Type::Float64,
+            Type::Float64,
+        ]
+    );
+
+    let Statement::VariableDecl { value, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    assert_eq!(
+        module.exprs[*value].kind,
+        Expression::Literal(Literal::UnsuffixedInt(3000000000))
+    );
+
+    let errors: Vec<_> = types
+        .errors
+        .iter()
+        .map(|x| format!("{}:{}: {}", x.line, x.column, x.message))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "2:9: literal out of range for `int32`",
+            "7:18: mismatched types: expected `float64`, found `int32`",
+        ]
+    );
+}
+
+#[test]
+fn types_the_booleans() {
+    let src = "let b: bool = true;\nlet c = false || b;\nlet d: int32 = true;\n";
+    let (module, types) = check(src);
+
+    let lets: Vec<_> = module.items.iter().map(|x| types.types[&x.id]).collect();
+    assert_eq!(lets, [Type::Bool, Type::Bool, Type::Int32]);
+
+    assert_eq!(
+        errors(src),
+        ["3:16: mismatched types: expected `int32`, found `bool`"]
+    );
+}
@@ -0,0 +1,117 @@
+use firelang::compiler::firelang_lexer::lexer::Lexer;
+use firelang::compiler::firelang_lexer::symbol::Symbol;
+use firelang::compiler::firelang_parser::ast::node::{
+    Behaviour, Expr, ExprId, Expression, Exprs, Statement, Stmt,
+};
+use firelang::compiler::firelang_parser::ast::token::{BinaryOp, Literal};
+use firelang::compiler::firelang_parser::ast::visit::{self, MutVisitor, Visitor};
+use firelang::compiler::firelang_parser::parser::Parser;
+
+const SOURCE: &str = r#"fn f(a <- int32, b -> int32) {
+    let c = a + b * 2;
+    let s = "c = {c + 1}";
+    { return g(c, 3 * 4); }
+}
+let x = ;
+"#;
+
+/// Collect the names used in the expressions, and count the errors.
+#[derive(Default)]
+struct Names {
+    idents: Vec<String>,
+    params: usize,
+    literals: usize,
+    errors: usize,
+}
+
+impl Visitor for Names {
+    fn visit_param(&mut self, _param: &(Symbol, Behaviour, Symbol)) {
+        self.params += 1;
+    }
+
+    fn visit_expr(&mut self, exprs: &Exprs, expr: &Expr) {
+        match &expr.kind {
+            Expression::Ident(x) | Expression::FuncCall { ident: x, .. } => {
+                self.idents.push(x.to_string())
+            }
+            Expression::Error => self.errors += 1,
+            _ => (),
+        }
+
+        visit::walk_expr(self, exprs, expr);
+    }
+
+    fn visit_literal(&mut self, _lit: &Literal) {
+        self.literals += 1;
+    }
+}
+
+#[test]
+fn visits_every_node() {
+    let module = Parser::new(Lexer::new(SOURCE)).parse_module();
+
+    let mut names = Names::default();
+    names.visit_module(&module);
+
+    assert_eq!(names.idents, ["a", "b", "c", "g", "c"]);
+    assert_eq!(names.params, 2);
+    // 2, 1, 3 and 4, the parts of the interpolated string are not literals.
+    assert_eq!(names.literals, 4);
+    assert_eq!(names.errors, 1);
+}
+
+/// Fold the products of two integers.
+struct Fold;
+
+impl MutVisitor for Fold {
+    fn visit_expr(&mut self, exprs: &mut Exprs, id: ExprId) {
+        visit::walk_expr_mut(self, exprs, id);
+
+        if let Expression::Binary {
+            lhs,
+            op: BinaryOp::Mul,
+            rhs,
+        } = &exprs[id].kind
+        {
+            if let (
+                Expression::Literal(Literal::UnsuffixedInt(a)),
+                Expression::Literal(Literal::UnsuffixedInt(b)),
+            ) = (&exprs[*lhs].kind, &exprs[*rhs].kind)
+            {
+                exprs[id].kind = Expression::Literal(Literal::UnsuffixedInt(a * b));
+            }
+        }
+    }
+}
+
+#[test]
+fn rewrites_in_place() {
+    let mut module = Parser::new(Lexer::new(SOURCE)).parse_module();
+    Fold.visit_module(&mut module);
+
+    let Statement::FuncDecl { body, .. } = &module.items[0].kind else {
+        panic!("{:?}", module.items[0]);
+    };
+    let Statement::Block(inner) = &body.block[2].kind else {
+        panic!("{:?}", body.block[2]);
+    };
+
+    let [Stmt {
+        kind: Statement::Return(x),
+        ..
+    }] = &inner.block[..]
+    else {
+        panic!("{:?}", inner.block);
+    };
+
+    assert!(matches!(
+        &module.exprs[*x].kind,
+        Expression::FuncCall { args, .. }
+            if module.exprs[args[1]].kind == Expression::Literal(Literal::UnsuffixedInt(12))
+    ));
+
+    // `b * 2` isn't folded.
+    let mut names = Names::default();
+    names.visit_module(&module);
+    assert_eq!(names.literals, 3);
+}